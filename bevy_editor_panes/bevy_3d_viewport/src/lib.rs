@@ -15,8 +15,8 @@ impl Plugin for Viewport3dPanePlugin {
     fn build(&self, app: &mut App) {
         app.world_mut()
             .get_resource_or_init::<PaneRegistry>()
-            .register("Viewport 3D", |mut commands, pane_root| {
-                commands.entity(pane_root).insert(Bevy3DViewport);
+            .register("Viewport 3D", |mut commands, pane| {
+                commands.entity(pane.root).insert(Bevy3DViewport);
             });
     }
 }
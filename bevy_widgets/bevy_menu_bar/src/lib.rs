@@ -11,12 +11,22 @@ use bevy_editor_styles::Theme;
 #[derive(Component)]
 pub struct MenuBarNode;
 
+/// The placeholder for the logo, at the start of the menu bar.
+#[derive(Component)]
+struct MenuBarLogo;
+
 /// The Bevy Menu Bar Plugin.
 pub struct MenuBarPlugin;
 
 impl Plugin for MenuBarPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, menu_setup.in_set(MenuBarSet));
+        app.add_systems(Startup, menu_setup.in_set(MenuBarSet))
+            .add_systems(
+                Update,
+                restyle_menu_bar
+                    .in_set(MenuBarSet)
+                    .run_if(resource_changed::<Theme>),
+            );
     }
 }
 
@@ -31,38 +41,53 @@ fn menu_setup(mut commands: Commands, root: Query<Entity, With<MenuBarNode>>, th
         .insert(NodeBundle {
             style: Style {
                 width: Val::Percent(100.0),
-                height: Val::Px(30.0),
+                height: Val::Px(theme.sizes.menu_bar),
                 display: Display::Flex,
                 flex_direction: FlexDirection::Row,
-                flex_basis: Val::Px(30.0),
+                flex_basis: Val::Px(theme.sizes.menu_bar),
                 justify_items: JustifyItems::Start,
                 align_items: AlignItems::Center,
-                padding: UiRect {
-                    left: Val::Px(5.0),
-                    right: Val::Px(5.0),
-                    top: Val::Px(0.0),
-                    bottom: Val::Px(0.0),
-                },
+                padding: UiRect::horizontal(Val::Px(theme.spacing.sm)),
                 ..Default::default()
             },
-            background_color: theme.background_color,
+            background_color: BackgroundColor(theme.colors.surface.background),
             ..Default::default()
         })
         .with_children(|parent| {
-            parent.spawn(NodeBundle {
-                style: Style {
-                    width: Val::Px(30.0),
-                    height: Val::Px(20.0),
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(theme.sizes.logo_width),
+                        height: Val::Px(theme.sizes.logo_height),
 
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(theme.colors.surface.sunken),
                     ..Default::default()
                 },
-                background_color: BackgroundColor(Color::Oklaba(Oklaba {
-                    lightness: 0.090,
-                    a: 0.0,
-                    b: 0.0,
-                    alpha: 1.0,
-                })),
-                ..Default::default()
-            });
+                MenuBarLogo,
+            ));
         });
 }
+
+/// Applies the [`Theme`] to the menu bar once it changes.
+fn restyle_menu_bar(
+    theme: Res<Theme>,
+    mut root: Query<(&mut BackgroundColor, &mut Style), With<MenuBarNode>>,
+    mut logo_query: Query<
+        (&mut BackgroundColor, &mut Style),
+        (With<MenuBarLogo>, Without<MenuBarNode>),
+    >,
+) {
+    for (mut background_color, mut style) in &mut root {
+        background_color.set_if_neq(BackgroundColor(theme.colors.surface.background));
+        style.height = Val::Px(theme.sizes.menu_bar);
+        style.flex_basis = Val::Px(theme.sizes.menu_bar);
+        style.padding = UiRect::horizontal(Val::Px(theme.spacing.sm));
+    }
+    for (mut background_color, mut style) in &mut logo_query {
+        background_color.set_if_neq(BackgroundColor(theme.colors.surface.sunken));
+        style.width = Val::Px(theme.sizes.logo_width);
+        style.height = Val::Px(theme.sizes.logo_height);
+    }
+}
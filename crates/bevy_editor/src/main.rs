@@ -13,7 +13,9 @@
 
 use bevy::prelude::*;
 
-use bevy_editor_styles::StylesPlugin;
+use bevy_editor_build::EditorBuildPlugin;
+use bevy_editor_settings::{EditorSettingsPlugin, SettingsAppExt};
+use bevy_editor_styles::{IconPlugin, StylesPlugin, UiSettings};
 
 // Panes
 use bevy_3d_viewport::Viewport3dPanePlugin;
@@ -22,9 +24,12 @@ mod ui;
 
 fn main() {
     App::new()
+        .add_plugins((DefaultPlugins, EditorSettingsPlugin))
+        .register_settings::<UiSettings>("ui")
         .add_plugins((
-            DefaultPlugins,
+            EditorBuildPlugin,
             StylesPlugin,
+            IconPlugin,
             Viewport3dPanePlugin,
             ui::EditorUIPlugin,
         ))
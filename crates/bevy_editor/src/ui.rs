@@ -1,15 +1,39 @@
 use bevy::prelude::*;
 
-use bevy_editor_styles::Theme;
+use bevy_editor_settings::{ActionTriggered, SettingsAppExt};
+use bevy_editor_styles::{IconRegistry, StyleClass, TextRole, Theme};
 use bevy_menu_bar::{MenuBarNode, MenuBarPlugin, MenuBarSet};
-use bevy_pane_layout::{PaneLayoutPlugin, PaneLayoutSet, RootPaneLayoutNode};
+use bevy_pane_layout::{
+    MovePaneFocus, PaneLayoutPlugin, PaneLayoutSet, ResetLayout, RootPaneLayoutNode,
+};
+
+/// The actions moving the focus between panes, with their default key bindings.
+const PANE_FOCUS_ACTIONS: [(&str, &str, MovePaneFocus); 6] = [
+    ("pane.focus_next", "Ctrl+Tab", MovePaneFocus::Next),
+    (
+        "pane.focus_previous",
+        "Ctrl+Shift+Tab",
+        MovePaneFocus::Previous,
+    ),
+    ("pane.focus_left", "Ctrl+Alt+Left", MovePaneFocus::Left),
+    ("pane.focus_right", "Ctrl+Alt+Right", MovePaneFocus::Right),
+    ("pane.focus_up", "Ctrl+Alt+Up", MovePaneFocus::Up),
+    ("pane.focus_down", "Ctrl+Alt+Down", MovePaneFocus::Down),
+];
 
 /// The Bevy Editor UI Plugin.
 pub struct EditorUIPlugin;
 
 impl Plugin for EditorUIPlugin {
     fn build(&self, app: &mut App) {
+        for (action, binding, _) in PANE_FOCUS_ACTIONS {
+            app.register_action(action, &[binding]);
+        }
+
         app.add_systems(Startup, ui_setup.in_set(UISet))
+            .add_systems(Startup, menu_bar_items.after(MenuBarSet))
+            .add_systems(Update, restyle_ui.run_if(resource_changed::<Theme>))
+            .add_systems(Update, pane_focus_actions.before(PaneLayoutSet))
             .configure_sets(Startup, (PaneLayoutSet, MenuBarSet).after(UISet))
             .add_plugins((PaneLayoutPlugin, MenuBarPlugin));
     }
@@ -22,7 +46,13 @@ pub struct UISet;
 #[derive(Component)]
 pub struct RootUINode;
 
-fn ui_setup(mut commands: Commands, theme: Res<Theme>) {
+fn ui_setup(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+    mut icons: ResMut<IconRegistry>,
+) {
+    icons.load_folder(&asset_server, "icons");
     commands.spawn(Camera2d);
 
     commands
@@ -37,7 +67,7 @@ fn ui_setup(mut commands: Commands, theme: Res<Theme>) {
 
                 ..Default::default()
             },
-            background_color: theme.background_color,
+            background_color: BackgroundColor(theme.colors.surface.background),
             ..Default::default()
         })
         .insert(RootUINode)
@@ -46,3 +76,52 @@ fn ui_setup(mut commands: Commands, theme: Res<Theme>) {
             parent.spawn(RootPaneLayoutNode);
         });
 }
+
+/// Adds the editor's actions to the menu bar, after its logo.
+fn menu_bar_items(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    menu_bar: Query<Entity, With<MenuBarNode>>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    margin: UiRect::left(Val::Px(theme.spacing.sm)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            StyleClass::new("button"),
+        ))
+        .observe(
+            |_trigger: Trigger<Pointer<Click>>, mut reset_events: EventWriter<ResetLayout>| {
+                reset_events.send(ResetLayout);
+            },
+        )
+        .set_parent(menu_bar.single())
+        .with_child((
+            Text::new("Reset Layout"),
+            theme.typography.font(TextRole::Label),
+            TextColor(theme.colors.text.primary),
+        ));
+}
+
+/// Applies the [`Theme`] to the root node once it changes.
+fn restyle_ui(theme: Res<Theme>, mut root: Query<&mut BackgroundColor, With<RootUINode>>) {
+    for mut background_color in &mut root {
+        background_color.set_if_neq(BackgroundColor(theme.colors.surface.background));
+    }
+}
+
+/// Moves the focus between panes when one of the [`PANE_FOCUS_ACTIONS`] is triggered.
+fn pane_focus_actions(
+    mut actions: EventReader<ActionTriggered>,
+    mut focus_events: EventWriter<MovePaneFocus>,
+) {
+    for ActionTriggered { action } in actions.read() {
+        if let Some((.., event)) = PANE_FOCUS_ACTIONS.iter().find(|(name, ..)| name == action) {
+            focus_events.send(*event);
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Building and running the project open in the editor.
+//!
+//! The commands come from the [`Build`](bevy_editor_settings::modals::workspace::Build) settings
+//! of the workspace, loaded by the [`EditorSettingsPlugin`](bevy_editor_settings::EditorSettingsPlugin).
+
+use bevy::prelude::*;
+
+mod runner;
+
+pub use bevy_editor_settings::BuildCommand;
+pub use runner::{
+    BuildDiagnostic, BuildEvent, BuildRunner, BuildStatus, CancelBuild, DiagnosticLevel,
+    DiagnosticLocation, OutputStream, RunBuild, RunLaunchProfile,
+};
+
+/// A Bevy plugin running the build commands and launch profiles of the workspace settings.
+///
+/// The [`EditorSettingsPlugin`](bevy_editor_settings::EditorSettingsPlugin) has to be added first.
+pub struct EditorBuildPlugin;
+
+impl Plugin for EditorBuildPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildRunner>()
+            .add_event::<RunBuild>()
+            .add_event::<RunLaunchProfile>()
+            .add_event::<CancelBuild>()
+            .add_event::<BuildEvent>()
+            .add_systems(Update, (runner::start_builds, runner::poll_builds).chain());
+    }
+}
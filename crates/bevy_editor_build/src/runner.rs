@@ -0,0 +1,594 @@
+//! Running the commands of the [`Build`](bevy_editor_settings::modals::workspace::Build) settings
+//! as child processes.
+//!
+//! Send [`RunBuild`] to start a command, [`RunLaunchProfile`] to run the project with a launch
+//! profile and [`CancelBuild`] to stop it. The output and the status of the command arrive as
+//! [`BuildEvent`]s. Cargo is asked for JSON messages, so the diagnostics of the compiler arrive as
+//! [`BuildDiagnostic`]s rather than lines of text.
+
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Mutex,
+    },
+};
+
+use bevy::prelude::*;
+use bevy_editor_settings::{modals::launch::LaunchProfile, BuildCommand, Settings};
+use serde::Deserialize;
+
+/// The cargo subcommands that can report their messages as JSON.
+const CARGO_SUBCOMMANDS: [&str; 5] = ["build", "check", "clippy", "run", "test"];
+
+/// Starts a [`BuildCommand`] in the directory of the workspace, cancelling the command that is running.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunBuild(pub BuildCommand);
+
+/// Runs the project with the launch profile called `name`, cancelling the command that is running.
+///
+/// `None` picks the default launch profile. The profile runs as [`BuildCommand::RunRelease`]
+/// if it builds in release mode, as [`BuildCommand::RunDebug`] otherwise.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct RunLaunchProfile(pub Option<String>);
+
+/// Cancels the running [`BuildCommand`].
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CancelBuild;
+
+/// The output and status of a [`BuildCommand`].
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub enum BuildEvent {
+    /// The command was started.
+    Started(BuildCommand),
+    /// The command wrote a line that is not a cargo message.
+    Output {
+        /// Where the line was written to.
+        stream: OutputStream,
+        /// The line, without the line break.
+        line: String,
+    },
+    /// The compiler reported a diagnostic.
+    Diagnostic(BuildDiagnostic),
+    /// The command stopped.
+    Finished {
+        /// The command that stopped.
+        command: BuildCommand,
+        /// How it stopped.
+        status: BuildStatus,
+    },
+}
+
+/// The output streams of a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputStream {
+    /// The standard output.
+    Stdout,
+    /// The standard error.
+    Stderr,
+}
+
+/// How a [`BuildCommand`] stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildStatus {
+    /// The command exited successfully.
+    Succeeded,
+    /// The command exited with an error, `code` is `None` if it was stopped by a signal.
+    Failed {
+        /// The exit code of the command.
+        code: Option<i32>,
+    },
+    /// The command was cancelled.
+    Cancelled,
+    /// The command could not be started.
+    SpawnFailed(String),
+}
+
+/// A diagnostic of the compiler, like an error or a warning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildDiagnostic {
+    /// How severe the diagnostic is.
+    pub level: DiagnosticLevel,
+    /// The main message of the diagnostic.
+    pub message: String,
+    /// The diagnostic as the compiler prints it, with the code it points at.
+    pub rendered: Option<String>,
+    /// Where the diagnostic points at in the code.
+    pub location: Option<DiagnosticLocation>,
+}
+
+/// How severe a [`BuildDiagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticLevel {
+    /// The build fails.
+    Error,
+    /// The build succeeds, but something is likely wrong.
+    Warning,
+    /// Additional information, like notes and help.
+    Note,
+}
+
+/// A position in a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticLocation {
+    /// The file, relative to the package.
+    pub file: PathBuf,
+    /// The line, starting at 1.
+    pub line: usize,
+    /// The column, starting at 1.
+    pub column: usize,
+}
+
+/// Runs the [`BuildCommand`]s, one at a time.
+#[derive(Resource, Default)]
+pub struct BuildRunner {
+    running: Option<RunningBuild>,
+}
+
+struct RunningBuild {
+    command: BuildCommand,
+    child: Child,
+    /// The lines written by the command, forwarded by a thread for each stream.
+    output: Mutex<Receiver<(OutputStream, String)>>,
+}
+
+impl BuildRunner {
+    /// The command that is running, if any.
+    pub fn running(&self) -> Option<BuildCommand> {
+        self.running.as_ref().map(|running| running.command)
+    }
+
+    /// Spawns `process` as `command`, forwarding its output.
+    fn start(&mut self, command: BuildCommand, mut process: Command) -> std::io::Result<()> {
+        let mut child = process
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let (sender, receiver) = mpsc::channel();
+        forward_lines(child.stdout.take(), OutputStream::Stdout, sender.clone());
+        forward_lines(child.stderr.take(), OutputStream::Stderr, sender);
+        self.running = Some(RunningBuild {
+            command,
+            child,
+            output: Mutex::new(receiver),
+        });
+        Ok(())
+    }
+
+    /// Kills the running command, returning it.
+    fn cancel(&mut self) -> Option<BuildCommand> {
+        let mut running = self.running.take()?;
+        if let Err(error) = running.child.kill().and_then(|()| running.child.wait()) {
+            warn!("Error stopping the {:?} build: {error}", running.command);
+        }
+        Some(running.command)
+    }
+}
+
+/// Sends the lines of `reader` to `sender` from a new thread, until the stream ends.
+fn forward_lines(
+    reader: Option<impl Read + Send + 'static>,
+    stream: OutputStream,
+    sender: Sender<(OutputStream, String)>,
+) {
+    let Some(reader) = reader else {
+        return;
+    };
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            if sender.send((stream, line)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// The process running `command_line` in `dir`.
+fn process(command_line: &str, dir: Option<&Path>) -> std::io::Result<Command> {
+    let arguments = command_arguments(command_line).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the build command has an unclosed quote",
+        )
+    })?;
+    let Some((program, arguments)) = arguments.split_first() else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the build command is empty",
+        ));
+    };
+
+    let mut process = Command::new(program);
+    process.args(arguments);
+    if let Some(dir) = dir {
+        process.current_dir(dir);
+    }
+    Ok(process)
+}
+
+/// The process running the project with `profile`, in the workspace at `root`.
+fn launch_process(profile: &LaunchProfile, root: Option<&Path>) -> Command {
+    let mut arguments = profile.cargo_arguments();
+    if let (Some(_), Some(root)) = (&profile.working_directory, root) {
+        // Cargo looks for the workspace from the directory it runs in
+        let manifest = root.join("Cargo.toml").display().to_string();
+        arguments.splice(1..1, ["--manifest-path".to_string(), manifest]);
+    }
+    arguments.insert(0, "cargo".to_string());
+    with_message_format(&mut arguments);
+
+    let mut process = Command::new(&arguments[0]);
+    process.args(&arguments[1..]).envs(&profile.env);
+    let dir = match (&profile.working_directory, root) {
+        (Some(dir), Some(root)) => Some(root.join(dir)),
+        (Some(dir), None) => Some(dir.clone()),
+        (None, root) => root.map(Path::to_path_buf),
+    };
+    if let Some(dir) = dir {
+        process.current_dir(dir);
+    }
+    process
+}
+
+/// Splits a command of the build settings like a shell does, asking cargo for JSON messages.
+///
+/// Returns `None` if a quote is not closed.
+fn command_arguments(command_line: &str) -> Option<Vec<String>> {
+    let mut arguments = shlex::split(command_line)?;
+    with_message_format(&mut arguments);
+    Some(arguments)
+}
+
+/// Asks cargo for JSON messages, if `arguments` run a cargo subcommand that doesn't choose a format.
+fn with_message_format(arguments: &mut Vec<String>) {
+    let is_cargo = arguments.first().is_some_and(|program| program == "cargo")
+        && arguments
+            .get(1)
+            .is_some_and(|subcommand| CARGO_SUBCOMMANDS.contains(&subcommand.as_str()));
+    let has_format = arguments
+        .iter()
+        .any(|argument| argument.starts_with("--message-format"));
+    if is_cargo && !has_format {
+        // Before any `--`, which passes the remaining arguments to the program
+        arguments.insert(2, "--message-format=json".to_string());
+    }
+}
+
+/// A line of the JSON messages of cargo.
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<CompilerMessage>,
+}
+
+#[derive(Deserialize)]
+struct CompilerMessage {
+    message: String,
+    level: String,
+    rendered: Option<String>,
+    #[serde(default)]
+    spans: Vec<CompilerSpan>,
+}
+
+#[derive(Deserialize)]
+struct CompilerSpan {
+    file_name: PathBuf,
+    line_start: usize,
+    column_start: usize,
+    is_primary: bool,
+}
+
+/// Turns a line written by a command into a [`BuildEvent`], `None` for cargo messages that are left out.
+fn parse_line(stream: OutputStream, line: String) -> Option<BuildEvent> {
+    let message = line
+        .starts_with('{')
+        .then(|| serde_json::from_str::<CargoMessage>(&line).ok())
+        .flatten();
+    let Some(message) = message else {
+        return Some(BuildEvent::Output { stream, line });
+    };
+
+    let compiler_message = message
+        .message
+        .filter(|_| message.reason == "compiler-message")?;
+    let level = if compiler_message.level.starts_with("error") {
+        DiagnosticLevel::Error
+    } else if compiler_message.level == "warning" {
+        DiagnosticLevel::Warning
+    } else {
+        DiagnosticLevel::Note
+    };
+    let location = compiler_message
+        .spans
+        .into_iter()
+        .find(|span| span.is_primary)
+        .map(|span| DiagnosticLocation {
+            file: span.file_name,
+            line: span.line_start,
+            column: span.column_start,
+        });
+
+    Some(BuildEvent::Diagnostic(BuildDiagnostic {
+        level,
+        message: compiler_message.message,
+        rendered: compiler_message.rendered,
+        location,
+    }))
+}
+
+/// Starts and cancels build commands on [`RunBuild`], [`RunLaunchProfile`] and [`CancelBuild`].
+pub(crate) fn start_builds(
+    mut run_events: EventReader<RunBuild>,
+    mut launch_events: EventReader<RunLaunchProfile>,
+    mut cancel_events: EventReader<CancelBuild>,
+    settings: Res<Settings>,
+    mut runner: ResMut<BuildRunner>,
+    mut events: EventWriter<BuildEvent>,
+) {
+    let cancel = !cancel_events.is_empty();
+    cancel_events.clear();
+    let run = run_events.read().last().copied();
+    let launch = launch_events.read().last().cloned();
+    if !cancel && run.is_none() && launch.is_none() {
+        return;
+    }
+
+    // Only one command runs at a time
+    if let Some(command) = runner.cancel() {
+        events.send(BuildEvent::Finished {
+            command,
+            status: BuildStatus::Cancelled,
+        });
+    }
+
+    let root = settings.project_root();
+    let (command, process) = match (launch, run) {
+        (Some(RunLaunchProfile(name)), _) => {
+            let profile = match &name {
+                Some(name) => settings
+                    .launch_profiles()
+                    .iter()
+                    .find(|profile| &profile.name == name),
+                None => settings.default_launch_profile(),
+            };
+            match profile {
+                Some(profile) if profile.release => {
+                    (BuildCommand::RunRelease, Ok(launch_process(profile, root)))
+                }
+                Some(profile) => (BuildCommand::RunDebug, Ok(launch_process(profile, root))),
+                None => (
+                    BuildCommand::RunDebug,
+                    Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("there is no launch profile {}", name.unwrap_or_default()),
+                    )),
+                ),
+            }
+        }
+        (None, Some(RunBuild(command))) => {
+            let build = settings
+                .workspace_settings()
+                .map(|workspace_settings| workspace_settings.build.clone())
+                .unwrap_or_default();
+            (command, process(build.command(command), root))
+        }
+        (None, None) => return,
+    };
+
+    match process.and_then(|process| runner.start(command, process)) {
+        Ok(()) => {
+            events.send(BuildEvent::Started(command));
+        }
+        Err(error) => {
+            error!("Error starting the {command:?} build: {error}");
+            events.send(BuildEvent::Finished {
+                command,
+                status: BuildStatus::SpawnFailed(error.to_string()),
+            });
+        }
+    }
+}
+
+/// Sends the output of the running command, and its status once it stopped.
+pub(crate) fn poll_builds(mut runner: ResMut<BuildRunner>, mut events: EventWriter<BuildEvent>) {
+    let Some(running) = &mut runner.running else {
+        return;
+    };
+
+    let output = running
+        .output
+        .get_mut()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let finished_output = loop {
+        match output.try_recv() {
+            Ok((stream, line)) => {
+                if let Some(event) = parse_line(stream, line) {
+                    events.send(event);
+                }
+            }
+            Err(TryRecvError::Empty) => break false,
+            Err(TryRecvError::Disconnected) => break true,
+        }
+    };
+    // Wait for all output, the process might still be exiting
+    if !finished_output {
+        return;
+    }
+    let status = match running.child.try_wait() {
+        Ok(None) => return,
+        Ok(Some(status)) if status.success() => BuildStatus::Succeeded,
+        Ok(Some(status)) => BuildStatus::Failed {
+            code: status.code(),
+        },
+        Err(error) => {
+            error!("Error waiting for the {:?} build: {error}", running.command);
+            BuildStatus::Failed { code: None }
+        }
+    };
+
+    events.send(BuildEvent::Finished {
+        command: running.command,
+        status,
+    });
+    runner.running = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cargo_arguments() {
+        assert_eq!(
+            command_arguments("cargo run --release -- --level 2"),
+            Some(
+                [
+                    "cargo",
+                    "run",
+                    "--message-format=json",
+                    "--release",
+                    "--",
+                    "--level",
+                    "2"
+                ]
+                .map(String::from)
+                .to_vec()
+            )
+        );
+        assert_eq!(
+            command_arguments("cargo build --message-format=short"),
+            Some(
+                ["cargo", "build", "--message-format=short"]
+                    .map(String::from)
+                    .to_vec()
+            )
+        );
+        assert_eq!(
+            command_arguments("make  all"),
+            Some(["make", "all"].map(String::from).to_vec())
+        );
+
+        // Quoted arguments are kept together
+        assert_eq!(
+            command_arguments(r#"cargo run -- --name "My Game" 'a b'"#),
+            Some(
+                [
+                    "cargo",
+                    "run",
+                    "--message-format=json",
+                    "--",
+                    "--name",
+                    "My Game",
+                    "a b"
+                ]
+                .map(String::from)
+                .to_vec()
+            )
+        );
+        assert_eq!(command_arguments(r#"cargo run -- "unclosed"#), None);
+    }
+
+    #[test]
+    fn build_process() {
+        let dir = Path::new("project");
+        let build = process("cargo build --release", Some(dir)).unwrap();
+        assert_eq!(build.get_program(), "cargo");
+        assert_eq!(
+            build.get_args().collect::<Vec<_>>(),
+            ["build", "--message-format=json", "--release"]
+        );
+        assert_eq!(build.get_current_dir(), Some(dir));
+
+        assert_eq!(
+            process("  ", None).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            process("cargo 'run", None).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn launch_profile_process() {
+        let profile = LaunchProfile {
+            name: "Editor".to_string(),
+            bin: Some("game".to_string()),
+            features: vec!["editor".to_string()],
+            args: vec!["--level".to_string(), "2".to_string()],
+            env: [("RUST_LOG".to_string(), "debug".to_string())].into(),
+            ..default()
+        };
+        let root = Path::new("project");
+        let launch = launch_process(&profile, Some(root));
+        assert_eq!(launch.get_program(), "cargo");
+        assert_eq!(
+            launch.get_args().collect::<Vec<_>>(),
+            [
+                "run",
+                "--message-format=json",
+                "--bin",
+                "game",
+                "--features",
+                "editor",
+                "--",
+                "--level",
+                "2"
+            ]
+        );
+        assert_eq!(
+            launch.get_envs().collect::<Vec<_>>(),
+            [("RUST_LOG".as_ref(), Some("debug".as_ref()))]
+        );
+        assert_eq!(launch.get_current_dir(), Some(root));
+
+        // Running in another directory still builds the workspace
+        let profile = LaunchProfile {
+            working_directory: Some(PathBuf::from("assets")),
+            ..profile
+        };
+        let launch = launch_process(&profile, Some(root));
+        let manifest = root.join("Cargo.toml").display().to_string();
+        assert_eq!(
+            launch.get_args().take(4).collect::<Vec<_>>(),
+            ["run", "--message-format=json", "--manifest-path", &manifest]
+        );
+        assert_eq!(
+            launch.get_current_dir(),
+            Some(root.join("assets").as_path())
+        );
+    }
+
+    #[test]
+    fn parse_cargo_messages() {
+        let line = r#"{"reason":"compiler-message","message":{"message":"unused variable: `x`","level":"warning","rendered":"warning: unused variable","spans":[{"file_name":"src/main.rs","line_start":3,"column_start":9,"is_primary":true}]}}"#;
+        assert_eq!(
+            parse_line(OutputStream::Stdout, line.to_string()),
+            Some(BuildEvent::Diagnostic(BuildDiagnostic {
+                level: DiagnosticLevel::Warning,
+                message: "unused variable: `x`".to_string(),
+                rendered: Some("warning: unused variable".to_string()),
+                location: Some(DiagnosticLocation {
+                    file: PathBuf::from("src/main.rs"),
+                    line: 3,
+                    column: 9,
+                }),
+            }))
+        );
+
+        // Other cargo messages are left out, the output of the program is kept
+        let line = r#"{"reason":"build-finished","success":true}"#;
+        assert_eq!(parse_line(OutputStream::Stdout, line.to_string()), None);
+        assert_eq!(
+            parse_line(OutputStream::Stderr, "{ not json".to_string()),
+            Some(BuildEvent::Output {
+                stream: OutputStream::Stderr,
+                line: "{ not json".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,566 @@
+//! Key bindings of the editor actions, configured in the `[keymap]` section of the settings files.
+//!
+//! ```toml
+//! [keymap]
+//! "pane.close" = "Ctrl+W"
+//! "settings.open" = ["Ctrl+Comma", "Ctrl+K Ctrl+S"]
+//! ```
+//!
+//! An action is bound to one or more bindings, each a sequence of chords separated by spaces.
+//! The user settings replace the bindings of the workspace settings, which replace the defaults,
+//! an empty list unbinds an action.
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use bevy::{
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, TypeInfo, Typed, VariantInfo},
+};
+
+use crate::{watcher::SettingsWatcher, SettingsChanged, SettingsLayer};
+
+/// The key of the keymap section in the settings files.
+const KEYMAP_KEY: &str = "keymap";
+/// How long the next chord of a sequence is waited for, in seconds.
+const SEQUENCE_TIMEOUT: f32 = 1.;
+/// The keys that only modify other keys.
+const MODIFIER_KEYS: [KeyCode; 8] = [
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::SuperLeft,
+    KeyCode::SuperRight,
+];
+/// Names of keys that are shorter than their [`KeyCode`], used both ways.
+const KEY_ALIASES: [(&str, KeyCode); 8] = [
+    ("Esc", KeyCode::Escape),
+    ("Up", KeyCode::ArrowUp),
+    ("Down", KeyCode::ArrowDown),
+    ("Left", KeyCode::ArrowLeft),
+    ("Right", KeyCode::ArrowRight),
+    ("Plus", KeyCode::Equal),
+    ("Del", KeyCode::Delete),
+    ("Return", KeyCode::Enter),
+];
+
+/// Sent when the key binding of an action was pressed.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct ActionTriggered {
+    /// The id of the action, like `pane.close`.
+    pub action: String,
+}
+
+/// Errors that can occur when reading a key binding.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum KeymapError {
+    /// The name does not match any key.
+    #[error("Unknown key {0}")]
+    UnknownKey(String),
+    /// A chord only has modifiers, like `Ctrl+`.
+    #[error("The chord {0} has no key")]
+    MissingKey(String),
+    /// The binding has no chords.
+    #[error("The key binding is empty")]
+    Empty,
+    /// The bindings of an action are not a string or a list of strings.
+    #[error("The bindings of {0} must be a string or a list of strings")]
+    InvalidBindings(String),
+}
+
+/// A key pressed along with modifiers, like `Ctrl+Shift+P`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct KeyChord {
+    /// Whether `Ctrl` is held.
+    pub ctrl: bool,
+    /// Whether `Shift` is held.
+    pub shift: bool,
+    /// Whether `Alt` is held.
+    pub alt: bool,
+    /// Whether `Super` (`Cmd` or the Windows key) is held.
+    pub super_key: bool,
+    /// The key that is pressed.
+    pub key: KeyCode,
+}
+
+impl KeyChord {
+    /// A chord of `key` without any modifiers.
+    pub fn new(key: KeyCode) -> Self {
+        Self {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            super_key: false,
+            key,
+        }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = KeymapError;
+
+    fn from_str(chord: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = chord.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+        if key.is_empty() {
+            return Err(KeymapError::MissingKey(chord.to_string()));
+        }
+
+        let mut result = Self::new(parse_key(key)?);
+        for modifier in parts {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => result.ctrl = true,
+                "shift" => result.shift = true,
+                "alt" | "option" => result.alt = true,
+                "super" | "cmd" | "command" | "meta" | "win" => result.super_key = true,
+                _ => return Err(KeymapError::UnknownKey(modifier.to_string())),
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (held, name) in [
+            (self.ctrl, "Ctrl+"),
+            (self.shift, "Shift+"),
+            (self.alt, "Alt+"),
+            (self.super_key, "Super+"),
+        ] {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        f.write_str(&key_name(self.key))
+    }
+}
+
+/// Reads the name of a key, like `A`, `1`, `F5`, `Esc` or any [`KeyCode`] variant like `KeyA`.
+fn parse_key(name: &str) -> Result<KeyCode, KeymapError> {
+    if let Some((_, key)) = KEY_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+    {
+        return Ok(*key);
+    }
+
+    let mut characters = name.chars();
+    let variant = match (characters.next(), characters.next()) {
+        (Some(letter), None) if letter.is_ascii_alphabetic() => {
+            format!("Key{}", letter.to_ascii_uppercase())
+        }
+        (Some(digit), None) if digit.is_ascii_digit() => format!("Digit{digit}"),
+        _ => name.to_string(),
+    };
+
+    // Any unit variant of `KeyCode` works
+    let is_unit_variant = matches!(
+        KeyCode::type_info(),
+        TypeInfo::Enum(info) if matches!(info.variant(&variant), Some(VariantInfo::Unit(_)))
+    );
+    is_unit_variant
+        .then(|| KeyCode::from_reflect(&DynamicEnum::new(variant, DynamicVariant::Unit)))
+        .flatten()
+        .ok_or_else(|| KeymapError::UnknownKey(name.to_string()))
+}
+
+/// The name of a key, the reverse of [`parse_key`].
+fn key_name(key: KeyCode) -> String {
+    if let Some((alias, _)) = KEY_ALIASES.iter().find(|(_, alias_key)| *alias_key == key) {
+        return (*alias).to_string();
+    }
+    let name = format!("{key:?}");
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .filter(|rest| rest.len() == 1)
+        .map_or_else(|| name.clone(), str::to_string)
+}
+
+/// One or more chords pressed one after the other, like `Ctrl+K Ctrl+S`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyBinding(pub Vec<KeyChord>);
+
+impl KeyBinding {
+    /// Whether pressing this binding passes through all of `other` first.
+    fn starts_with(&self, other: &KeyBinding) -> bool {
+        self.0.starts_with(&other.0)
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = KeymapError;
+
+    fn from_str(binding: &str) -> Result<Self, Self::Err> {
+        let chords = binding
+            .split_whitespace()
+            .map(KeyChord::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        if chords.is_empty() {
+            return Err(KeymapError::Empty);
+        }
+        Ok(Self(chords))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, chord) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{chord}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Two actions whose bindings collide: pressing the first binding triggers its action
+/// before the second binding is complete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeymapConflict {
+    /// The actions, only the first one is triggered unless the bindings are the same.
+    pub actions: [String; 2],
+    /// The bindings of the actions, the first one is the same as the second or starts it.
+    pub bindings: [KeyBinding; 2],
+}
+
+impl fmt::Display for KeymapConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} hides {} of {}",
+            self.bindings[0], self.actions[0], self.bindings[1], self.actions[1]
+        )
+    }
+}
+
+/// The key bindings of the actions, sending [`ActionTriggered`] when one is pressed.
+#[derive(Resource, Default, Debug)]
+pub struct Keymap {
+    /// The bindings registered by the plugins.
+    defaults: BTreeMap<String, Vec<KeyBinding>>,
+    /// The bindings merged from the defaults and the settings files.
+    bindings: BTreeMap<String, Vec<KeyBinding>>,
+    conflicts: Vec<KeymapConflict>,
+    /// The chords of the sequence being pressed.
+    pending: Vec<KeyChord>,
+    last_press: Option<f32>,
+}
+
+impl Keymap {
+    /// The bindings of `action`.
+    pub fn bindings(&self, action: &str) -> &[KeyBinding] {
+        self.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// All actions with their bindings.
+    pub fn actions(&self) -> impl Iterator<Item = (&str, &[KeyBinding])> {
+        self.bindings
+            .iter()
+            .map(|(action, bindings)| (action.as_str(), bindings.as_slice()))
+    }
+
+    /// The bindings that collide, found when the keymap was loaded.
+    pub fn conflicts(&self) -> &[KeymapConflict] {
+        &self.conflicts
+    }
+
+    /// Sets the default bindings of `action`, used unless the settings files bind it.
+    pub(crate) fn set_default(&mut self, action: String, bindings: Vec<KeyBinding>) {
+        self.defaults.insert(action, bindings);
+    }
+
+    /// Merges the bindings from the defaults and the `[keymap]` sections of the settings files,
+    /// reporting invalid bindings and conflicts.
+    pub(crate) fn load(&mut self, workspace: Option<&toml::Table>, user: Option<&toml::Table>) {
+        self.bindings = self.defaults.clone();
+        for table in [workspace, user].into_iter().flatten() {
+            if let Some(toml::Value::Table(section)) = table.get(KEYMAP_KEY) {
+                read_bindings(section, "", &mut self.bindings);
+            }
+        }
+        self.bindings.retain(|_, bindings| !bindings.is_empty());
+        self.pending.clear();
+
+        self.conflicts = find_conflicts(&self.bindings);
+        for conflict in &self.conflicts {
+            warn!("Conflicting key bindings: {conflict}");
+        }
+    }
+
+    /// Adds a pressed chord to the sequence being pressed, returning the actions it completes.
+    fn press(&mut self, chord: KeyChord, now: f32) -> Vec<String> {
+        if self
+            .last_press
+            .is_some_and(|last_press| now - last_press > SEQUENCE_TIMEOUT)
+        {
+            self.pending.clear();
+        }
+        self.last_press = Some(now);
+        self.pending.push(chord);
+
+        loop {
+            let pending = KeyBinding(self.pending.clone());
+            let actions: Vec<String> = self
+                .actions()
+                .filter(|(_, bindings)| bindings.contains(&pending))
+                .map(|(action, _)| action.to_string())
+                .collect();
+            if !actions.is_empty() {
+                self.pending.clear();
+                return actions;
+            }
+            let is_partial = self
+                .bindings
+                .values()
+                .flatten()
+                .any(|binding| binding.starts_with(&pending));
+            if is_partial {
+                return Vec::new();
+            }
+
+            // The chord might start another binding
+            if self.pending.len() == 1 {
+                self.pending.clear();
+                return Vec::new();
+            }
+            self.pending = vec![chord];
+        }
+    }
+}
+
+/// Reads the bindings of the actions in a `[keymap]` section, nested tables are joined with dots.
+fn read_bindings(
+    section: &toml::Table,
+    prefix: &str,
+    bindings: &mut BTreeMap<String, Vec<KeyBinding>>,
+) {
+    for (key, value) in section {
+        let action = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+
+        let strings = match value {
+            toml::Value::Table(table) => {
+                read_bindings(table, &action, bindings);
+                continue;
+            }
+            toml::Value::String(binding) if binding.trim().is_empty() => Some(Vec::new()),
+            toml::Value::String(binding) => Some(vec![binding.as_str()]),
+            toml::Value::Array(array) => array.iter().map(toml::Value::as_str).collect(),
+            _ => None,
+        };
+        let Some(strings) = strings else {
+            warn!("{}", KeymapError::InvalidBindings(action));
+            continue;
+        };
+
+        let parsed = strings
+            .into_iter()
+            .filter_map(|binding| {
+                binding
+                    .parse()
+                    .inspect_err(|error| {
+                        warn!("Invalid key binding {binding} of {action}: {error}");
+                    })
+                    .ok()
+            })
+            .collect();
+        bindings.insert(action, parsed);
+    }
+}
+
+/// Finds the bindings of different actions where one is the same as the other or starts it.
+fn find_conflicts(bindings: &BTreeMap<String, Vec<KeyBinding>>) -> Vec<KeymapConflict> {
+    let all: Vec<(&String, &KeyBinding)> = bindings
+        .iter()
+        .flat_map(|(action, bindings)| bindings.iter().map(move |binding| (action, binding)))
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (index, (first_action, first)) in all.iter().enumerate() {
+        for (second_action, second) in &all[index + 1..] {
+            if first_action == second_action {
+                continue;
+            }
+            let (winner, hidden) = if second.starts_with(first) {
+                ((first_action, first), (second_action, second))
+            } else if first.starts_with(second) {
+                ((second_action, second), (first_action, first))
+            } else {
+                continue;
+            };
+            conflicts.push(KeymapConflict {
+                actions: [winner.0.to_string(), hidden.0.to_string()],
+                bindings: [(*winner.1).clone(), (*hidden.1).clone()],
+            });
+        }
+    }
+    conflicts
+}
+
+/// Sends [`ActionTriggered`] for the bindings that were pressed.
+pub(crate) fn trigger_actions(
+    input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+    mut keymap: ResMut<Keymap>,
+    mut events: EventWriter<ActionTriggered>,
+) {
+    for key in input.get_just_pressed() {
+        if MODIFIER_KEYS.contains(key) {
+            continue;
+        }
+        let chord = KeyChord {
+            ctrl: input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]),
+            shift: input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            alt: input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+            super_key: input.any_pressed([KeyCode::SuperLeft, KeyCode::SuperRight]),
+            key: *key,
+        };
+        for action in keymap.press(chord, time.elapsed_secs()) {
+            events.send(ActionTriggered { action });
+        }
+    }
+}
+
+/// Loads the keymap once the actions are registered, reporting invalid bindings and conflicts once.
+pub(crate) fn load_keymap(watcher: Res<SettingsWatcher>, mut keymap: ResMut<Keymap>) {
+    keymap.load(
+        watcher.table(SettingsLayer::Workspace),
+        watcher.table(SettingsLayer::User),
+    );
+}
+
+/// Loads the keymap again when the `[keymap]` section of a settings file changed.
+pub(crate) fn reload_keymap(
+    mut events: EventReader<SettingsChanged>,
+    watcher: Res<SettingsWatcher>,
+    mut keymap: ResMut<Keymap>,
+) {
+    let prefix = format!("{KEYMAP_KEY}.");
+    let changed = events
+        .read()
+        .any(|event| event.keys.iter().any(|key| key.starts_with(&prefix)));
+    // The events after the first match don't change anything
+    events.clear();
+    if changed {
+        keymap.load(
+            watcher.table(SettingsLayer::Workspace),
+            watcher.table(SettingsLayer::User),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(binding: &str) -> KeyBinding {
+        binding.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_bindings() {
+        assert_eq!(
+            binding("ctrl+Shift+p"),
+            KeyBinding(vec![KeyChord {
+                ctrl: true,
+                shift: true,
+                ..KeyChord::new(KeyCode::KeyP)
+            }])
+        );
+        assert_eq!(binding("Ctrl+K Ctrl+S").0.len(), 2);
+        assert_eq!(binding("Cmd+ArrowUp").to_string(), "Super+Up");
+        assert_eq!(binding("Alt+1 F5 Esc").to_string(), "Alt+1 F5 Esc");
+
+        assert_eq!(
+            "Ctrl+Nope".parse::<KeyBinding>(),
+            Err(KeymapError::UnknownKey("Nope".to_string()))
+        );
+        assert_eq!(
+            "Ctrl+".parse::<KeyBinding>(),
+            Err(KeymapError::MissingKey("Ctrl+".to_string()))
+        );
+        assert_eq!("  ".parse::<KeyBinding>(), Err(KeymapError::Empty));
+    }
+
+    #[test]
+    fn layered_keymap() {
+        let mut keymap = Keymap::default();
+        keymap.set_default("pane.close".to_string(), vec![binding("Ctrl+W")]);
+        keymap.set_default("pane.split".to_string(), vec![binding("Ctrl+Backslash")]);
+
+        let workspace: toml::Table =
+            toml::from_str("[keymap]\npane.close = \"Ctrl+Q\"\n\"settings.open\" = \"Ctrl+K\"")
+                .unwrap();
+        let user: toml::Table = toml::from_str(
+            "[keymap]\n\"pane.split\" = []\n\"settings.keymap\" = \"Ctrl+K Ctrl+S\"",
+        )
+        .unwrap();
+        keymap.load(Some(&workspace), Some(&user));
+
+        assert_eq!(keymap.bindings("pane.close"), [binding("Ctrl+Q")]);
+        assert!(keymap.bindings("pane.split").is_empty());
+        assert_eq!(
+            keymap.conflicts(),
+            [KeymapConflict {
+                actions: ["settings.open".to_string(), "settings.keymap".to_string()],
+                bindings: [binding("Ctrl+K"), binding("Ctrl+K Ctrl+S")],
+            }]
+        );
+    }
+
+    #[test]
+    fn press_sequences() {
+        let mut keymap = Keymap::default();
+        keymap.set_default(
+            "settings.keymap".to_string(),
+            vec![binding("Ctrl+K Ctrl+S")],
+        );
+        keymap.set_default("pane.close".to_string(), vec![binding("Ctrl+W")]);
+        keymap.load(None, None);
+        let ctrl = |key| KeyChord {
+            ctrl: true,
+            ..KeyChord::new(key)
+        };
+
+        assert!(keymap.press(ctrl(KeyCode::KeyK), 0.).is_empty());
+        assert_eq!(keymap.press(ctrl(KeyCode::KeyS), 0.5), ["settings.keymap"]);
+
+        // A chord that doesn't continue the sequence starts over
+        assert!(keymap.press(ctrl(KeyCode::KeyK), 1.).is_empty());
+        assert_eq!(keymap.press(ctrl(KeyCode::KeyW), 1.2), ["pane.close"]);
+
+        // Sequences time out
+        assert!(keymap.press(ctrl(KeyCode::KeyK), 2.).is_empty());
+        assert!(keymap.press(ctrl(KeyCode::KeyS), 4.).is_empty());
+    }
+
+    #[test]
+    fn load_registered_actions_at_startup() {
+        use crate::SettingsAppExt;
+
+        let user: toml::Table = toml::from_str("[keymap]\n\"pane.close\" = \"Ctrl+Q\"").unwrap();
+        let mut app = App::new();
+        app.insert_resource(SettingsWatcher::new(None, None, Some(&user)))
+            .init_resource::<Keymap>()
+            .add_systems(Startup, load_keymap)
+            .register_action("pane.close", &["Ctrl+W"])
+            .register_action("pane.split", &["Ctrl+Backslash"]);
+        assert!(app
+            .world()
+            .resource::<Keymap>()
+            .bindings("pane.close")
+            .is_empty());
+
+        app.update();
+        let keymap = app.world().resource::<Keymap>();
+        assert_eq!(keymap.bindings("pane.close"), [binding("Ctrl+Q")]);
+        assert_eq!(keymap.bindings("pane.split"), [binding("Ctrl+Backslash")]);
+    }
+}
@@ -0,0 +1,226 @@
+//! Discovering the launch profiles of the workspace with `cargo metadata`.
+
+use std::{path::Path, process::Command};
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task},
+};
+use serde::Deserialize;
+
+use crate::{modals::launch::LaunchProfile, Settings};
+
+/// Discovers the launch profiles of the open project in the background, as `cargo metadata` is slow.
+#[derive(Resource, Default)]
+pub(crate) struct LaunchProfileDiscovery {
+    task: Option<Task<Vec<LaunchProfile>>>,
+}
+
+impl LaunchProfileDiscovery {
+    /// Starts discovering the launch profiles of the open project, unless its workspace settings list some.
+    ///
+    /// A discovery that is still running is dropped, it was for the previous project.
+    pub(crate) fn start(&mut self, settings: &Settings) {
+        self.task = None;
+        let Some(root) = settings.project_root() else {
+            return;
+        };
+        if settings
+            .workspace_settings()
+            .is_some_and(|workspace_settings| !workspace_settings.launch.is_empty())
+        {
+            return;
+        }
+
+        let root = root.to_path_buf();
+        self.task =
+            Some(AsyncComputeTaskPool::get().spawn(async move { discover_launch_profiles(&root) }));
+    }
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    targets: Vec<Target>,
+}
+
+#[derive(Deserialize)]
+struct Target {
+    name: String,
+    kind: Vec<String>,
+}
+
+/// Creates a launch profile for each binary and example of the packages of the workspace in `dir`.
+///
+/// Returns no profiles if cargo can't describe the workspace.
+pub(crate) fn discover_launch_profiles(dir: &Path) -> Vec<LaunchProfile> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo)
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .current_dir(dir)
+        .output();
+
+    let output = match output {
+        Ok(output) if output.status.success() => output,
+        Ok(output) => {
+            warn!(
+                "Could not discover the launch profiles: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return Vec::new();
+        }
+        Err(error) => {
+            warn!("Could not discover the launch profiles: {error}");
+            return Vec::new();
+        }
+    };
+
+    launch_profiles_from_metadata(&output.stdout).unwrap_or_else(|error| {
+        warn!("Could not read the output of `cargo metadata`: {error}");
+        Vec::new()
+    })
+}
+
+/// Discovers the launch profiles of the project the editor starts with.
+pub(crate) fn start_launch_profile_discovery(
+    settings: Res<Settings>,
+    mut discovery: ResMut<LaunchProfileDiscovery>,
+) {
+    discovery.start(&settings);
+}
+
+/// Sets the discovered launch profiles once `cargo metadata` is done.
+pub(crate) fn poll_launch_profile_discovery(
+    mut discovery: ResMut<LaunchProfileDiscovery>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some(task) = &mut discovery.task else {
+        return;
+    };
+    let Some(profiles) = block_on(future::poll_once(task)) else {
+        return;
+    };
+    discovery.task = None;
+    settings.discovered_launch_profiles = profiles;
+}
+
+/// Creates the launch profiles of the packages described by the output of `cargo metadata`.
+fn launch_profiles_from_metadata(metadata: &[u8]) -> serde_json::Result<Vec<LaunchProfile>> {
+    let metadata: Metadata = serde_json::from_slice(metadata)?;
+
+    let mut binaries = Vec::new();
+    let mut examples = Vec::new();
+    for package in metadata.packages {
+        for target in package.targets {
+            let is = |kind: &str| target.kind.iter().any(|target_kind| target_kind == kind);
+            if is("bin") {
+                binaries.push(LaunchProfile {
+                    name: target.name.clone(),
+                    package: Some(package.name.clone()),
+                    bin: Some(target.name),
+                    ..default()
+                });
+            } else if is("example") {
+                examples.push(LaunchProfile {
+                    name: format!("{} (example)", target.name),
+                    package: Some(package.name.clone()),
+                    example: Some(target.name),
+                    ..default()
+                });
+            }
+        }
+    }
+
+    // Binaries come first, so they are picked by default
+    binaries.extend(examples);
+    Ok(binaries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_from_metadata() {
+        let metadata = r#"{
+            "packages": [
+                {
+                    "name": "game",
+                    "targets": [
+                        { "name": "showcase", "kind": ["example"] },
+                        { "name": "game", "kind": ["bin"] },
+                        { "name": "game", "kind": ["lib"] }
+                    ]
+                }
+            ]
+        }"#;
+
+        let profiles = launch_profiles_from_metadata(metadata.as_bytes()).unwrap();
+        assert_eq!(
+            profiles
+                .iter()
+                .map(|profile| profile.name.as_str())
+                .collect::<Vec<_>>(),
+            ["game", "showcase (example)"]
+        );
+        assert_eq!(
+            profiles[1].cargo_arguments(),
+            ["run", "--package", "game", "--example", "showcase"]
+        );
+    }
+
+    #[test]
+    fn discover_in_background() {
+        let mut app = App::new();
+        app.add_plugins(TaskPoolPlugin::default())
+            .init_resource::<LaunchProfileDiscovery>()
+            .add_systems(Update, poll_launch_profile_discovery);
+
+        // Workspaces listing their profiles are not discovered
+        let workspace: crate::modals::workspace::WorkspaceSettings =
+            toml::from_str("[editor_settings]\n[build]\n[[launch]]\nname = \"Game\"").unwrap();
+        let mut settings = Settings::from_layers(None, None, default());
+        settings.set_workspace_settings(Some(workspace));
+        settings.project_root = Some(std::env::temp_dir());
+        app.world_mut()
+            .resource_mut::<LaunchProfileDiscovery>()
+            .start(&settings);
+        assert!(app
+            .world()
+            .resource::<LaunchProfileDiscovery>()
+            .task
+            .is_none());
+
+        settings.set_launch_profiles(Vec::new());
+        app.insert_resource(settings);
+        app.world_mut()
+            .resource_mut::<LaunchProfileDiscovery>()
+            .task = Some(AsyncComputeTaskPool::get().spawn(async {
+            vec![LaunchProfile {
+                name: "discovered".to_string(),
+                ..default()
+            }]
+        }));
+        for _ in 0..100 {
+            app.update();
+            if app
+                .world()
+                .resource::<LaunchProfileDiscovery>()
+                .task
+                .is_none()
+            {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert_eq!(
+            app.world().resource::<Settings>().launch_profiles()[0].name,
+            "discovered"
+        );
+    }
+}
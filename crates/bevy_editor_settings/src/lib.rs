@@ -1,50 +1,327 @@
 //! A straightforward way to store and retrieve user preferences on disk for Bevy applications.
 
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    reflect::GetPath,
+    utils::{HashMap, HashSet},
+};
 
+mod keymap;
+mod launch;
+mod merge;
+mod migration;
 pub mod modals;
 mod persistent;
+mod project;
+mod registration;
+#[cfg(feature = "schema")]
+mod schema;
+mod watcher;
+
+pub use keymap::{ActionTriggered, KeyBinding, KeyChord, Keymap, KeymapConflict, KeymapError};
+pub use merge::MergeError;
+pub use migration::{SettingsMigrations, SETTINGS_VERSION};
+pub use modals::workspace::BuildCommand;
+pub use persistent::USER_CONFIG_DIR_ENV;
+pub use project::{find_project_root, OpenProject, ProjectOpened, WORKSPACE_SETTINGS_FILE};
+pub use registration::SettingsAppExt;
+#[cfg(feature = "schema")]
+pub use schema::SettingsSchemas;
+pub use watcher::SettingsChanged;
 
 /// A Bevy plugin for editor settings.
 /// This plugin loads the workspace settings, user settings, and project settings.
 pub struct EditorSettingsPlugin;
 
+/// A source of settings, ordered from the lowest to the highest precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+pub enum SettingsLayer {
+    /// The default project settings, chosen by the plugins or the editor.
+    Project,
+    /// The workspace settings from `Bevy.toml`.
+    Workspace,
+    /// The user settings from `user.toml`.
+    User,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Resource, Reflect)]
 /// Represents the settings for the editor.
 /// This includes workspace settings, user settings, and project settings.
 pub struct Settings {
     /// Settings for the workspace
-    pub workspace_settings: Option<modals::workspace::WorkspaceSettings>,
+    workspace_settings: Option<modals::workspace::WorkspaceSettings>,
     /// Settings for the user
-    pub user_settings: Option<modals::user::UserSettings>,
+    user_settings: Option<modals::user::UserSettings>,
     /// default project settings used when no workspace or user settings are present for a given setting
-    pub project_settings: modals::project::ProjectSettings,
+    default_project_settings: modals::project::ProjectSettings,
+    /// The paths of the project settings set in the workspace settings file.
+    #[reflect(ignore)]
+    workspace_fields: HashSet<String>,
+    /// The paths of the project settings set in the user settings file.
+    #[reflect(ignore)]
+    user_fields: HashSet<String>,
+    /// The project settings merged from all layers.
+    #[reflect(ignore)]
+    merged: modals::project::ProjectSettings,
+    /// The layer each merged path came from, paths that are missing come from the project settings.
+    #[reflect(ignore)]
+    sources: HashMap<String, SettingsLayer>,
+    /// The launch profiles found in the workspace, used when the workspace settings don't list any.
+    #[reflect(ignore)]
+    discovered_launch_profiles: Vec<modals::launch::LaunchProfile>,
+    /// The root directory of the open project, holding the workspace settings file.
+    #[reflect(ignore)]
+    project_root: Option<std::path::PathBuf>,
 }
 
 impl Settings {
-    /// Get the project settings.
+    /// Creates the settings from the layers that were loaded, along with the TOML tables they were read from.
+    ///
+    /// The tables tell which fields a layer actually sets, only those override the layers below.
+    pub(crate) fn from_layers(
+        workspace: Option<(modals::workspace::WorkspaceSettings, toml::Table)>,
+        user: Option<(modals::user::UserSettings, toml::Table)>,
+        project_settings: modals::project::ProjectSettings,
+    ) -> Self {
+        let mut settings = Self {
+            workspace_settings: None,
+            user_settings: None,
+            merged: project_settings.clone(),
+            default_project_settings: project_settings,
+            workspace_fields: HashSet::new(),
+            user_fields: HashSet::new(),
+            sources: HashMap::new(),
+            discovered_launch_profiles: Vec::new(),
+            project_root: None,
+        };
+        settings.set_workspace_layer(workspace);
+        settings.set_user_layer(user);
+        settings
+    }
+
+    /// Replaces the workspace layer, along with the TOML table it was read from.
+    pub(crate) fn set_workspace_layer(
+        &mut self,
+        workspace: Option<(modals::workspace::WorkspaceSettings, toml::Table)>,
+    ) {
+        self.workspace_fields = layer_fields(workspace.as_ref(), "editor_settings", |settings| {
+            &settings.editor_settings
+        });
+        self.workspace_settings = workspace.map(|(settings, _)| settings);
+        self.merge();
+    }
+
+    /// Replaces the user layer, along with the TOML table it was read from.
+    pub(crate) fn set_user_layer(
+        &mut self,
+        user: Option<(modals::user::UserSettings, toml::Table)>,
+    ) {
+        self.user_fields = layer_fields(user.as_ref(), "project_settings", |settings| {
+            &settings.project_settings
+        });
+        self.user_settings = user.map(|(settings, _)| settings);
+        self.merge();
+    }
+
+    /// Get the project settings, merged field by field.
     ///
-    /// TODO this needs to do some kind of merging of settings
     /// the order of precedence should be from highest to lowest:
     /// 1. user settings
     /// 2. workspace settings
     /// 3. default project settings
     pub fn project_settings(&self) -> &modals::project::ProjectSettings {
+        &self.merged
+    }
+
+    /// The settings of the workspace, `None` if there is no workspace settings file.
+    pub fn workspace_settings(&self) -> Option<&modals::workspace::WorkspaceSettings> {
+        self.workspace_settings.as_ref()
+    }
+
+    /// The settings of the user, `None` if there is no user settings file.
+    pub fn user_settings(&self) -> Option<&modals::user::UserSettings> {
+        self.user_settings.as_ref()
+    }
+
+    /// The default project settings, used for the settings the workspace and user don't set.
+    pub fn default_project_settings(&self) -> &modals::project::ProjectSettings {
+        &self.default_project_settings
+    }
+
+    /// Replaces the workspace settings, which override every default project setting.
+    pub fn set_workspace_settings(
+        &mut self,
+        workspace_settings: Option<modals::workspace::WorkspaceSettings>,
+    ) {
+        self.set_workspace_layer(workspace_settings.map(whole_layer));
+    }
+
+    /// Replaces the user settings, which override every project setting of the lower layers.
+    pub fn set_user_settings(&mut self, user_settings: Option<modals::user::UserSettings>) {
+        self.set_user_layer(user_settings.map(whole_layer));
+    }
+
+    /// Replaces the default project settings.
+    pub fn set_default_project_settings(
+        &mut self,
+        project_settings: modals::project::ProjectSettings,
+    ) {
+        self.default_project_settings = project_settings;
+        self.merge();
+    }
+
+    /// Returns the layer the project setting at `path` (like `"name"`) came from,
+    /// or `None` if there is no such setting.
+    pub fn setting_source(&self, path: &str) -> Option<SettingsLayer> {
+        self.merged.reflect_path(path).ok()?;
+
+        // Settings set as a whole come from the layer that set their parent
+        let mut prefix = path;
+        loop {
+            if let Some(layer) = self.sources.get(prefix) {
+                return Some(*layer);
+            }
+            match prefix.rfind('.') {
+                Some(index) => prefix = &prefix[..index],
+                None => return Some(SettingsLayer::Project),
+            }
+        }
+    }
+
+    /// Sets the project setting at `path` in the user settings, overriding the other layers.
+    pub fn set_user_setting(
+        &mut self,
+        path: &str,
+        value: &dyn PartialReflect,
+    ) -> Result<(), MergeError> {
+        let field = self
+            .user_settings_mut()
+            .project_settings
+            .reflect_path_mut(path)
+            .map_err(|error| MergeError::Path(error.to_string()))?;
+        field.try_apply(value)?;
+
+        self.user_fields.insert(path.to_string());
+        self.merge();
+        Ok(())
+    }
+
+    /// The user settings, created without overriding any project settings if there is no user file.
+    fn user_settings_mut(&mut self) -> &mut modals::user::UserSettings {
+        self.user_settings
+            .get_or_insert_with(|| modals::user::UserSettings {
+                project_settings: self.merged.clone(),
+                recent_projects: Vec::new(),
+            })
+    }
+
+    /// The root directory of the open project, `None` if no project was found.
+    pub fn project_root(&self) -> Option<&std::path::Path> {
+        self.project_root.as_deref()
+    }
+
+    /// The root directories of the projects opened last, the latest first.
+    pub fn recent_projects(&self) -> &[std::path::PathBuf] {
         self.user_settings
             .as_ref()
-            .map(|settings| &settings.project_settings)
-            .or_else(|| {
-                self.workspace_settings
-                    .as_ref()
-                    .map(|settings| &settings.editor_settings)
+            .map_or(&[], |user_settings| &user_settings.recent_projects)
+    }
+
+    /// Adds a project to the front of the recent projects of the user settings.
+    pub(crate) fn add_recent_project(&mut self, root: std::path::PathBuf) {
+        project::push_recent_project(&mut self.user_settings_mut().recent_projects, root);
+    }
+
+    /// Recomputes the merged project settings from the layers.
+    fn merge(&mut self) {
+        self.merged = self.default_project_settings.clone();
+        self.sources.clear();
+
+        if let Some(workspace_settings) = &self.workspace_settings {
+            merge::apply_layer(
+                &mut self.merged,
+                &workspace_settings.editor_settings,
+                &self.workspace_fields,
+                SettingsLayer::Workspace,
+                &mut self.sources,
+            );
+        }
+        if let Some(user_settings) = &self.user_settings {
+            merge::apply_layer(
+                &mut self.merged,
+                &user_settings.project_settings,
+                &self.user_fields,
+                SettingsLayer::User,
+                &mut self.sources,
+            );
+        }
+    }
+
+    /// The ways to run the project.
+    ///
+    /// These are the `[[launch]]` profiles of the workspace settings,
+    /// or a profile for each binary and example of the workspace if there are none.
+    pub fn launch_profiles(&self) -> &[modals::launch::LaunchProfile] {
+        match &self.workspace_settings {
+            Some(workspace_settings) if !workspace_settings.launch.is_empty() => {
+                &workspace_settings.launch
+            }
+            _ => &self.discovered_launch_profiles,
+        }
+    }
+
+    /// The launch profile selected in the workspace settings, or the first one.
+    pub fn default_launch_profile(&self) -> Option<&modals::launch::LaunchProfile> {
+        let profiles = self.launch_profiles();
+        self.workspace_settings
+            .as_ref()
+            .and_then(|workspace_settings| workspace_settings.default_launch.as_ref())
+            .and_then(|name| profiles.iter().find(|profile| &profile.name == name))
+            .or_else(|| profiles.first())
+    }
+
+    /// Replaces the `[[launch]]` profiles of the workspace settings.
+    pub fn set_launch_profiles(&mut self, profiles: Vec<modals::launch::LaunchProfile>) {
+        self.workspace_settings_mut().launch = profiles;
+    }
+
+    /// Selects the launch profile called `name` as the default one, `None` picks the first one.
+    pub fn set_default_launch_profile(&mut self, name: Option<String>) {
+        self.workspace_settings_mut().default_launch = name;
+    }
+
+    /// Save the launch profiles to the workspace settings, leaving the rest of the file alone.
+    pub fn save_launch_profiles(&self) -> Result<(), persistent::PersistentError> {
+        let (launch, default_launch) = match &self.workspace_settings {
+            Some(workspace_settings) => (
+                workspace_settings.launch.as_slice(),
+                workspace_settings.default_launch.as_deref(),
+            ),
+            None => (&[][..], None),
+        };
+        let root = self
+            .project_root()
+            .ok_or(persistent::PersistentError::WorkspaceConfigDirs)?;
+        persistent::save_launch_profiles(root, launch, default_launch)
+    }
+
+    /// The workspace settings, created without overriding any project settings if there is no workspace file.
+    fn workspace_settings_mut(&mut self) -> &mut modals::workspace::WorkspaceSettings {
+        self.workspace_settings
+            .get_or_insert_with(|| modals::workspace::WorkspaceSettings {
+                editor_settings: self.default_project_settings.clone(),
+                build: default(),
+                launch: Vec::new(),
+                default_launch: None,
             })
-            .unwrap_or(&self.project_settings)
     }
 
     /// Save the user settings.
+    ///
+    /// Only the settings the user has set are written, so the other layers keep applying to the rest.
     pub fn save_user_settings(&self) -> Result<(), persistent::PersistentError> {
         if let Some(user_settings) = &self.user_settings {
-            persistent::save_user_settings(user_settings)?;
+            persistent::save_user_settings(user_settings, &self.user_fields)?;
             Ok(())
         } else {
             warn!("No user settings to save.");
@@ -53,25 +330,236 @@ impl Settings {
     }
 }
 
+/// Pairs `settings` with a table setting all of its fields.
+fn whole_layer<T: serde::Serialize>(settings: T) -> (T, toml::Table) {
+    let Ok(toml::Value::Table(table)) = toml::Value::try_from(&settings) else {
+        unreachable!("the settings are structs of TOML values");
+    };
+    (settings, table)
+}
+
+/// Collects the paths of the project settings a layer sets, from the table under `key`.
+fn layer_fields<T>(
+    layer: Option<&(T, toml::Table)>,
+    key: &str,
+    project_settings: impl Fn(&T) -> &modals::project::ProjectSettings,
+) -> HashSet<String> {
+    let mut fields = HashSet::new();
+    if let Some((settings, table)) = layer {
+        if let Some(toml::Value::Table(table)) = table.get(key) {
+            merge::collect_paths(project_settings(settings), table, "", &mut fields);
+        }
+    }
+    fields
+}
+
 impl Plugin for EditorSettingsPlugin {
     fn build(&self, app: &mut App) {
-        let workspace_settings = persistent::load_workspace_settings()
-            .inspect_err(|error| {
-                error!("Error loading workspace settings: {:?}", error);
-            })
-            .ok();
-        let user_settings = persistent::load_user_settings()
+        app.init_resource::<SettingsMigrations>();
+        let migrations = app.world().resource::<SettingsMigrations>();
+
+        let project_root = project::start_dir().and_then(|dir| find_project_root(&dir));
+        let workspace_settings = project_root
+            .as_deref()
+            .and_then(|root| project::load_workspace_layer(root, migrations));
+        let user_settings = persistent::load_user_settings(migrations)
             .inspect_err(|error| {
-                error!("Error loading user settings: {:?}", error);
+                error!("Error loading user settings: {error}");
             })
             .ok();
 
         let project_settings = modals::project::ProjectSettings::default();
 
-        app.insert_resource(Settings {
-            workspace_settings,
-            user_settings,
+        let watcher = watcher::SettingsWatcher::new(
+            project_root
+                .as_deref()
+                .map(persistent::workspace_settings_path),
+            workspace_settings.as_ref().map(|(_, table)| table),
+            user_settings.as_ref().map(|(_, table)| table),
+        );
+
+        let mut settings =
+            Settings::from_layers(workspace_settings, user_settings, project_settings);
+        settings.project_root = project_root;
+
+        app.insert_resource(settings)
+            .insert_resource(watcher)
+            .init_resource::<Keymap>()
+            .add_event::<SettingsChanged>()
+            .add_event::<ActionTriggered>()
+            .add_event::<OpenProject>()
+            .add_event::<ProjectOpened>()
+            .init_resource::<launch::LaunchProfileDiscovery>()
+            .add_systems(
+                Startup,
+                (keymap::load_keymap, launch::start_launch_profile_discovery),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        project::open_projects,
+                        watcher::reload_changed_settings,
+                        keymap::reload_keymap,
+                    )
+                        .chain(),
+                    keymap::trigger_actions.run_if(resource_exists::<ButtonInput<KeyCode>>),
+                    launch::poll_launch_profile_discovery,
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use modals::{project::ProjectSettings, user::UserSettings, workspace::WorkspaceSettings};
+
+    fn layer<T: serde::de::DeserializeOwned>(source: &str) -> (T, toml::Table) {
+        let table: toml::Table = toml::from_str(source).unwrap();
+        (toml::Value::Table(table.clone()).try_into().unwrap(), table)
+    }
+
+    fn name(settings: &Settings) -> &str {
+        settings.project_settings().path::<String>("name").unwrap()
+    }
+
+    #[test]
+    fn field_level_merging() {
+        let workspace = layer::<WorkspaceSettings>(
+            r#"
+            [editor_settings]
+            name = "Workspace Project"
+            [build]
+            "#,
+        );
+
+        // The user layer exists but doesn't set the name
+        let user = layer::<UserSettings>("[project_settings]");
+        let settings = Settings::from_layers(
+            Some(workspace.clone()),
+            Some(user),
+            ProjectSettings::default(),
+        );
+        assert_eq!(name(&settings), "Workspace Project");
+        assert_eq!(
+            settings.setting_source("name"),
+            Some(SettingsLayer::Workspace)
+        );
+
+        let user = layer::<UserSettings>(
+            r#"
+            [project_settings]
+            name = "User Project"
+            "#,
+        );
+        let settings =
+            Settings::from_layers(Some(workspace), Some(user), ProjectSettings::default());
+        assert_eq!(name(&settings), "User Project");
+        assert_eq!(settings.setting_source("name"), Some(SettingsLayer::User));
+
+        let settings = Settings::from_layers(None, None, ProjectSettings::default());
+        assert_eq!(name(&settings), "My Project");
+        assert_eq!(
+            settings.setting_source("name"),
+            Some(SettingsLayer::Project)
+        );
+        assert_eq!(settings.setting_source("missing"), None);
+    }
+
+    #[test]
+    fn launch_profiles() {
+        use modals::launch::LaunchProfile;
+
+        let workspace = layer::<WorkspaceSettings>(
+            r#"
+            default_launch = "Editor"
+            [editor_settings]
+            [build]
+            [[launch]]
+            name = "Game"
+            bin = "game"
+            [[launch]]
+            name = "Editor"
+            bin = "game"
+            features = ["editor"]
+            env = { RUST_LOG = "debug" }
+            "#,
+        );
+        let mut settings = Settings::from_layers(Some(workspace), None, ProjectSettings::default());
+        settings.discovered_launch_profiles = vec![LaunchProfile {
+            name: "discovered".to_string(),
+            ..default()
+        }];
+
+        let profile = settings.default_launch_profile().unwrap();
+        assert_eq!(profile.name, "Editor");
+        assert_eq!(profile.env["RUST_LOG"], "debug");
+
+        // Unknown defaults fall back to the first profile
+        settings.set_default_launch_profile(Some("Missing".to_string()));
+        assert_eq!(settings.default_launch_profile().unwrap().name, "Game");
+
+        // Without profiles in the workspace settings the discovered ones are used
+        settings.set_launch_profiles(Vec::new());
+        assert_eq!(
+            settings.default_launch_profile().unwrap().name,
+            "discovered"
+        );
+    }
+
+    #[test]
+    fn set_user_setting() {
+        let workspace = layer::<WorkspaceSettings>(
+            r#"
+            [editor_settings]
+            name = "Workspace Project"
+            [build]
+            "#,
+        );
+        let mut settings = Settings::from_layers(Some(workspace), None, ProjectSettings::default());
+
+        settings
+            .set_user_setting("name", &"User Project".to_string())
+            .unwrap();
+        assert_eq!(name(&settings), "User Project");
+        assert_eq!(settings.setting_source("name"), Some(SettingsLayer::User));
+
+        assert!(settings.set_user_setting("name", &5_u32).is_err());
+        assert!(settings.set_user_setting("missing", &5_u32).is_err());
+    }
+
+    #[test]
+    fn setters_merge() {
+        let mut settings = Settings::from_layers(None, None, ProjectSettings::default());
+
+        let mut project_settings = ProjectSettings::default();
+        *project_settings.path_mut::<String>("name").unwrap() = "Default Project".to_string();
+        settings.set_default_project_settings(project_settings.clone());
+        assert_eq!(name(&settings), "Default Project");
+
+        *project_settings.path_mut::<String>("name").unwrap() = "Workspace Project".to_string();
+        settings.set_workspace_settings(Some(WorkspaceSettings {
+            editor_settings: project_settings.clone(),
+            build: default(),
+            launch: Vec::new(),
+            default_launch: None,
+        }));
+        assert_eq!(name(&settings), "Workspace Project");
+        assert_eq!(
+            settings.setting_source("name"),
+            Some(SettingsLayer::Workspace)
+        );
+
+        *project_settings.path_mut::<String>("name").unwrap() = "User Project".to_string();
+        settings.set_user_settings(Some(UserSettings {
             project_settings,
-        });
+            recent_projects: Vec::new(),
+        }));
+        assert_eq!(name(&settings), "User Project");
+
+        settings.set_user_settings(None);
+        assert_eq!(name(&settings), "Workspace Project");
+        assert!(settings.user_settings().is_none());
     }
 }
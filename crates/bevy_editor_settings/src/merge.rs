@@ -0,0 +1,81 @@
+//! Field level merging of the settings layers through reflection.
+
+use bevy::{
+    reflect::{ApplyError, GetPath, PartialReflect, ReflectRef},
+    utils::{HashMap, HashSet},
+};
+
+use crate::SettingsLayer;
+
+/// Errors that can occur when changing a setting by its path.
+#[derive(Debug, thiserror::Error)]
+pub enum MergeError {
+    /// The path does not lead to a setting.
+    #[error("Invalid settings path: {0}")]
+    Path(String),
+    /// The value does not fit the setting.
+    #[error("Error applying the setting: {0}")]
+    Apply(#[from] ApplyError),
+    /// The value could not be read from a settings file.
+    #[error("Error reading the setting: {0}")]
+    Deserialize(#[from] toml::de::Error),
+    /// The type of the setting is missing from the type registry.
+    #[error("The type {0} of the setting is not registered")]
+    Unregistered(String),
+}
+
+/// Collects the paths of the fields of `value` that are set in `table`.
+///
+/// Nested structs are followed into, any other field counts as set as a whole.
+pub(crate) fn collect_paths(
+    value: &dyn PartialReflect,
+    table: &toml::Table,
+    prefix: &str,
+    paths: &mut HashSet<String>,
+) {
+    let ReflectRef::Struct(value) = value.reflect_ref() else {
+        return;
+    };
+
+    for (key, item) in table {
+        // Unknown keys were ignored when deserializing
+        let Some(field) = value.field(key) else {
+            continue;
+        };
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+
+        match item {
+            toml::Value::Table(table) if matches!(field.reflect_ref(), ReflectRef::Struct(_)) => {
+                collect_paths(field, table, &path, paths);
+            }
+            _ => {
+                paths.insert(path);
+            }
+        }
+    }
+}
+
+/// Copies the fields at `paths` from `layer_value` onto `target`, recording `layer` as their source.
+pub(crate) fn apply_layer<T: GetPath>(
+    target: &mut T,
+    layer_value: &T,
+    paths: &HashSet<String>,
+    layer: SettingsLayer,
+    sources: &mut HashMap<String, SettingsLayer>,
+) {
+    for path in paths {
+        let (Ok(field), Ok(value)) = (
+            target.reflect_path_mut(path.as_str()),
+            layer_value.reflect_path(path.as_str()),
+        ) else {
+            continue;
+        };
+        if field.try_apply(value).is_ok() {
+            sources.insert(path.clone(), layer);
+        }
+    }
+}
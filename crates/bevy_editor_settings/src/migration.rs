@@ -0,0 +1,196 @@
+//! Versioning of the settings files, and migrating older files to the current schema.
+
+use bevy::prelude::*;
+
+use crate::{persistent::PersistentError, SettingsLayer};
+
+/// The version of the settings files written by this version of the editor.
+///
+/// Files without a `version` key were written before versioning, and are version 0.
+/// Bump it along with registering the migrations from the previous version.
+pub const SETTINGS_VERSION: u32 = 0;
+
+/// The key holding the version of a settings file.
+pub(crate) const VERSION_KEY: &str = "version";
+
+/// A registry of migrations that upgrade older settings files to the current schema.
+///
+/// Register migrations before adding the [`EditorSettingsPlugin`](crate::EditorSettingsPlugin),
+/// so they apply to the files it loads.
+#[derive(Resource)]
+pub struct SettingsMigrations {
+    migrations: Vec<Migration>,
+    /// The version documents are upgraded to, [`SETTINGS_VERSION`] outside of tests.
+    version: u32,
+}
+
+impl Default for SettingsMigrations {
+    fn default() -> Self {
+        Self {
+            migrations: Vec::new(),
+            version: SETTINGS_VERSION,
+        }
+    }
+}
+
+struct Migration {
+    layer: SettingsLayer,
+    from_version: u32,
+    migrate: Box<dyn Fn(&mut toml::Table) + Send + Sync>,
+}
+
+impl SettingsMigrations {
+    /// Migrations upgrading documents to `version` instead of [`SETTINGS_VERSION`].
+    #[cfg(test)]
+    pub(crate) fn with_version(version: u32) -> Self {
+        Self {
+            version,
+            ..default()
+        }
+    }
+
+    /// Register a migration upgrading the files of `layer` from `from_version` to the next version.
+    ///
+    /// Migrations for the same version run in the order they were registered.
+    pub fn register(
+        &mut self,
+        layer: SettingsLayer,
+        from_version: u32,
+        migrate: impl Fn(&mut toml::Table) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.migrations.push(Migration {
+            layer,
+            from_version,
+            migrate: Box::new(migrate),
+        });
+        self
+    }
+
+    /// Upgrades `document` to [`SETTINGS_VERSION`].
+    ///
+    /// Returns the version the document had if a migration ran, the document is left alone otherwise.
+    pub fn migrate(
+        &self,
+        layer: SettingsLayer,
+        document: &mut toml::Table,
+    ) -> Result<Option<u32>, PersistentError> {
+        let version = document_version(document)?;
+        if version > self.version {
+            return Err(PersistentError::UnsupportedVersion {
+                found: version,
+                supported: self.version,
+            });
+        }
+
+        let mut migrated = false;
+        for from_version in version..self.version {
+            for migration in self.migrations.iter().filter(|migration| {
+                migration.layer == layer && migration.from_version == from_version
+            }) {
+                (migration.migrate)(document);
+                migrated = true;
+            }
+        }
+        if !migrated {
+            return Ok(None);
+        }
+        document.insert(
+            VERSION_KEY.to_string(),
+            toml::Value::Integer(self.version.into()),
+        );
+
+        Ok(Some(version))
+    }
+}
+
+/// Reads the version of a settings file.
+fn document_version(document: &toml::Table) -> Result<u32, PersistentError> {
+    match document.get(VERSION_KEY) {
+        None => Ok(0),
+        Some(toml::Value::Integer(version)) => {
+            u32::try_from(*version).map_err(|_| PersistentError::InvalidVersion)
+        }
+        Some(_) => Err(PersistentError::InvalidVersion),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_older_document() {
+        let mut migrations = SettingsMigrations::with_version(1);
+        migrations.register(SettingsLayer::User, 0, |document| {
+            if let Some(settings) = document.remove("project") {
+                document.insert("project_settings".to_string(), settings);
+            }
+        });
+        // Migrations of other files are left out
+        migrations.register(SettingsLayer::Workspace, 0, |document| {
+            document.clear();
+        });
+
+        let mut document: toml::Table = toml::from_str("[project]\nname = \"Old\"").unwrap();
+        assert_eq!(
+            migrations
+                .migrate(SettingsLayer::User, &mut document)
+                .unwrap(),
+            Some(0)
+        );
+        assert_eq!(document["project_settings"]["name"].as_str(), Some("Old"));
+        assert_eq!(document[VERSION_KEY].as_integer(), Some(1));
+
+        // Current documents are left alone
+        let before = document.clone();
+        assert_eq!(
+            migrations
+                .migrate(SettingsLayer::User, &mut document)
+                .unwrap(),
+            None
+        );
+        assert_eq!(document, before);
+    }
+
+    #[test]
+    fn nothing_to_migrate() {
+        // Without migrations for a layer, its documents are not touched, not even their version
+        let mut migrations = SettingsMigrations::with_version(1);
+        migrations.register(SettingsLayer::Workspace, 0, |document| {
+            document.clear();
+        });
+
+        let mut document: toml::Table = toml::from_str("[project_settings]").unwrap();
+        let before = document.clone();
+        assert_eq!(
+            migrations
+                .migrate(SettingsLayer::User, &mut document)
+                .unwrap(),
+            None
+        );
+        assert_eq!(document, before);
+        assert_eq!(
+            SettingsMigrations::default()
+                .migrate(SettingsLayer::Workspace, &mut document)
+                .unwrap(),
+            None
+        );
+        assert_eq!(document, before);
+    }
+
+    #[test]
+    fn newer_document() {
+        let mut document: toml::Table =
+            toml::from_str(&format!("version = {}", SETTINGS_VERSION + 1)).unwrap();
+        assert!(matches!(
+            SettingsMigrations::default().migrate(SettingsLayer::User, &mut document),
+            Err(PersistentError::UnsupportedVersion { .. })
+        ));
+
+        let mut document: toml::Table = toml::from_str("version = \"one\"").unwrap();
+        assert!(matches!(
+            SettingsMigrations::default().migrate(SettingsLayer::User, &mut document),
+            Err(PersistentError::InvalidVersion)
+        ));
+    }
+}
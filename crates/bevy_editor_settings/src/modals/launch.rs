@@ -0,0 +1,63 @@
+//! Launch profiles, the ways to run the project
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use bevy::reflect::Reflect;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize, Reflect)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+/// A way to run the project, stored in the `[[launch]]` list of the workspace settings
+pub struct LaunchProfile {
+    /// The name of the profile
+    pub name: String,
+    /// The cargo package to run, the default package of the workspace if `None`
+    pub package: Option<String>,
+    /// The binary to run
+    pub bin: Option<String>,
+    /// The example to run, instead of a binary
+    pub example: Option<String>,
+    /// Whether to build in release mode
+    pub release: bool,
+    /// The cargo features to enable
+    pub features: Vec<String>,
+    /// Whether to disable the default features
+    pub no_default_features: bool,
+    /// The arguments passed to the program
+    pub args: Vec<String>,
+    /// The environment variables set for the program
+    pub env: BTreeMap<String, String>,
+    /// The directory to run the program in, relative to the workspace
+    pub working_directory: Option<PathBuf>,
+}
+
+impl LaunchProfile {
+    /// The arguments of `cargo` to run the profile.
+    pub fn cargo_arguments(&self) -> Vec<String> {
+        let mut arguments = vec!["run".to_string()];
+        if let Some(package) = &self.package {
+            arguments.extend(["--package".to_string(), package.clone()]);
+        }
+        if let Some(bin) = &self.bin {
+            arguments.extend(["--bin".to_string(), bin.clone()]);
+        }
+        if let Some(example) = &self.example {
+            arguments.extend(["--example".to_string(), example.clone()]);
+        }
+        if self.release {
+            arguments.push("--release".to_string());
+        }
+        if !self.features.is_empty() {
+            arguments.extend(["--features".to_string(), self.features.join(",")]);
+        }
+        if self.no_default_features {
+            arguments.push("--no-default-features".to_string());
+        }
+        if !self.args.is_empty() {
+            arguments.push("--".to_string());
+            arguments.extend(self.args.iter().cloned());
+        }
+        arguments
+    }
+}
@@ -1,6 +1,7 @@
 //! Modals for the editor settings.
 
 pub mod editor;
+pub mod launch;
 pub mod project;
 pub mod user;
 pub mod workspace;
@@ -1,5 +1,7 @@
 //! this is for the user to override workspace settings
 
+use std::path::PathBuf;
+
 use bevy::reflect::Reflect;
 use serde::{Deserialize, Serialize};
 
@@ -11,4 +13,7 @@ use super::project::ProjectSettings;
 pub struct UserSettings {
     /// project settings for the user
     pub project_settings: ProjectSettings,
+    /// the projects opened last, the latest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_projects: Vec<PathBuf>,
 }
@@ -3,7 +3,7 @@
 use bevy::reflect::Reflect;
 use serde::{Deserialize, Serialize};
 
-use super::project::ProjectSettings;
+use super::{launch::LaunchProfile, project::ProjectSettings};
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Reflect)]
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
@@ -14,6 +14,12 @@ pub struct WorkspaceSettings {
     pub editor_settings: ProjectSettings,
     /// Settings for building the project
     pub build: Build,
+    /// The ways to run the project, discovered from `cargo metadata` if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub launch: Vec<LaunchProfile>,
+    /// The name of the launch profile used by default, the first one if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_launch: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Reflect)]
@@ -41,3 +47,28 @@ impl Default for Build {
         }
     }
 }
+
+/// One of the commands of the [`Build`] settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum BuildCommand {
+    /// Build the project in debug mode.
+    Debug,
+    /// Build the project in release mode.
+    Release,
+    /// Run the project in debug mode.
+    RunDebug,
+    /// Run the project in release mode.
+    RunRelease,
+}
+
+impl Build {
+    /// The command line of `command`.
+    pub fn command(&self, command: BuildCommand) -> &str {
+        match command {
+            BuildCommand::Debug => &self.debug,
+            BuildCommand::Release => &self.release,
+            BuildCommand::RunDebug => &self.run_debug,
+            BuildCommand::RunRelease => &self.run_release,
+        }
+    }
+}
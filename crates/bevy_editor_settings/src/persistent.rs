@@ -1,60 +1,722 @@
-/// Load a type implementing `serde::Deserialize` from a TOML file.
-pub fn load<T>(path: impl AsRef<std::path::Path>) -> Result<T, PersistentError>
+/// Read the TOML file at `path`, along with the table it holds.
+pub(crate) fn read(path: &std::path::Path) -> Result<(String, toml::Table), PersistentError> {
+    let file = std::fs::read_to_string(path).map_err(|error| PersistentError::io(path, error))?;
+    let table =
+        toml::from_str(&file).map_err(|error| PersistentError::parse(path, &file, error))?;
+    Ok((file, table))
+}
+
+/// Load a settings file of `layer`, along with the table it was read from.
+///
+/// The table tells which fields are actually set in the file. It is migrated to the current schema
+/// before deserializing. When a migration changed it, the original file is backed up next to it
+/// before the migrated version is written.
+pub(crate) fn load_settings_file<T>(
+    path: &std::path::Path,
+    layer: crate::SettingsLayer,
+    migrations: &crate::SettingsMigrations,
+) -> Result<(T, toml::Table), PersistentError>
 where
     T: serde::de::DeserializeOwned,
 {
-    let path = path.as_ref();
-    let file = std::fs::read_to_string(path).unwrap();
-    Ok(toml::from_str(&file)?)
+    let (mut file, original) = read(path)?;
+
+    let mut table = original.clone();
+    if let Some(version) = migrations.migrate(layer, &mut table)? {
+        let mut backup_name = path.file_name().unwrap_or_default().to_os_string();
+        backup_name.push(format!(".v{version}.bak"));
+        let backup_path = path.with_file_name(backup_name);
+
+        std::fs::copy(path, &backup_path)
+            .map_err(|error| PersistentError::io(&backup_path, error))?;
+        file = write_document(path, &file, &original, &table)?;
+        bevy::log::info!(
+            "Migrated {} from version {version}, the original was backed up to {}",
+            path.display(),
+            backup_path.display()
+        );
+    }
+
+    deserialize_fields(path, &file, table)
+}
+
+/// Writes `new` to `path`, where `file` held `old`, returning the written file.
+///
+/// The document of `file` is edited rather than written anew, so the comments and formatting
+/// of the settings that did not change are kept.
+fn write_document(
+    path: &std::path::Path,
+    file: &str,
+    old: &toml::Table,
+    new: &toml::Table,
+) -> Result<String, PersistentError> {
+    let mut document: toml_edit::DocumentMut = file
+        .parse()
+        .map_err(|error| PersistentError::edit(path, file, error))?;
+    update_document(document.as_table_mut(), old, new);
+
+    let file = document.to_string();
+    std::fs::write(path, &file).map_err(|error| PersistentError::io(path, error))?;
+    Ok(file)
+}
+
+/// Edits `document`, which holds `old`, into holding `new`.
+///
+/// The items of the values that are the same in both are left alone.
+fn update_document(document: &mut toml_edit::Table, old: &toml::Table, new: &toml::Table) {
+    document.retain(|key, _| new.contains_key(key));
+    for (key, value) in new {
+        let old_value = old.get(key);
+        if old_value == Some(value) {
+            continue;
+        }
+        match (document.get_mut(key), old_value, value) {
+            (
+                Some(toml_edit::Item::Table(document)),
+                Some(toml::Value::Table(old)),
+                toml::Value::Table(new),
+            ) => update_document(document, old, new),
+            _ => {
+                document.insert(key, document_item(value));
+            }
+        }
+    }
+}
+
+/// Converts `value` to a document item, writing tables and arrays of tables as sections.
+fn document_item(value: &toml::Value) -> toml_edit::Item {
+    match value {
+        toml::Value::Table(table) => {
+            let mut document = toml_edit::Table::new();
+            update_document(&mut document, &toml::Table::new(), table);
+            toml_edit::Item::Table(document)
+        }
+        toml::Value::Array(array)
+            if !array.is_empty() && array.iter().all(toml::Value::is_table) =>
+        {
+            let tables = array
+                .iter()
+                .filter_map(|value| match document_item(value) {
+                    toml_edit::Item::Table(table) => Some(table),
+                    _ => None,
+                })
+                .collect();
+            toml_edit::Item::ArrayOfTables(tables)
+        }
+        _ => toml_edit::Item::Value(
+            value
+                .to_string()
+                .parse()
+                .expect("TOML values are written as valid TOML"),
+        ),
+    }
+}
+
+/// Deserializes `table`, parsed from `file`, leaving out the settings that don't fit so they fall
+/// back to their defaults.
+///
+/// Returns the table without the settings that were left out.
+fn deserialize_fields<T>(
+    path: &std::path::Path,
+    file: &str,
+    table: toml::Table,
+) -> Result<(T, toml::Table), PersistentError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    // Deserializing the file rather than the table keeps the position of the error
+    let error = match toml::from_str(file) {
+        Ok(value) => return Ok((value, table)),
+        Err(error) => error,
+    };
+
+    // Add the settings one by one onto the tables of the file, keeping the ones that fit
+    let deserialize = |table: &toml::Table| toml::Value::Table(table.clone()).try_into::<T>();
+    let mut valid = bare_tables(&table);
+    if deserialize(&valid).is_err() {
+        return Err(PersistentError::invalid(path, file, error));
+    }
+    let document = toml_edit::ImDocument::parse(file).ok();
+    let mut settings = Vec::new();
+    collect_settings(&table, &[], &mut settings);
+    for (parents, key, value) in settings {
+        let parent = table_at(&mut valid, &parents);
+        parent.insert(key.clone(), value);
+        if let Err(setting_error) = deserialize(&valid) {
+            table_at(&mut valid, &parents).remove(&key);
+            let keys: Vec<_> = parents.iter().chain(Some(&key)).cloned().collect();
+            let (line, column) = setting_position(file, document.as_ref(), &keys);
+            bevy::log::warn!(
+                "Invalid setting {} in {}:{line}:{column}, using the default: {}",
+                keys.join("."),
+                path.display(),
+                setting_error.message()
+            );
+        }
+    }
+
+    let value = deserialize(&valid).map_err(|_| PersistentError::invalid(path, file, error))?;
+    Ok((value, valid))
+}
+
+/// The line and column of the setting at `keys` in `file`, which `document` was parsed from.
+fn setting_position(
+    file: &str,
+    document: Option<&toml_edit::ImDocument<&str>>,
+    keys: &[String],
+) -> (usize, usize) {
+    let span = document.and_then(|document| {
+        keys.iter()
+            .try_fold(document.as_item(), |item, key| item.get(key.as_str()))
+            .and_then(toml_edit::Item::span)
+    });
+    position(file, span)
+}
+
+/// Copies the nested tables of `table`, without any of the settings in them.
+fn bare_tables(table: &toml::Table) -> toml::Table {
+    table
+        .iter()
+        .filter_map(|(key, value)| match value {
+            toml::Value::Table(table) => {
+                Some((key.clone(), toml::Value::Table(bare_tables(table))))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Lists the settings of `table` that are not tables, along with the keys of the tables they are in.
+fn collect_settings(
+    table: &toml::Table,
+    parents: &[String],
+    settings: &mut Vec<(Vec<String>, String, toml::Value)>,
+) {
+    for (key, value) in table {
+        match value {
+            toml::Value::Table(table) => {
+                let mut parents = parents.to_vec();
+                parents.push(key.clone());
+                collect_settings(table, &parents, settings);
+            }
+            _ => settings.push((parents.to_vec(), key.clone(), value.clone())),
+        }
+    }
+}
+
+/// Follows `keys` through the nested tables of `table`, which all have to exist.
+fn table_at<'a>(table: &'a mut toml::Table, keys: &[String]) -> &'a mut toml::Table {
+    keys.iter()
+        .fold(table, |table, key| match table.get_mut(key) {
+            Some(toml::Value::Table(table)) => table,
+            _ => unreachable!("the bare tables hold every table of the file"),
+        })
+}
+
+/// The environment variable to override the directory the user settings are stored in.
+pub const USER_CONFIG_DIR_ENV: &str = "BEVY_EDITOR_CONFIG_DIR";
+
+/// The directory the user settings are stored in, unless overridden with [`USER_CONFIG_DIR_ENV`].
+///
+/// - Linux: `$XDG_CONFIG_HOME/bevy_editor` or `$HOME/.config/bevy_editor`
+/// - Windows: `{FOLDERID_RoamingAppData}\bevy\bevy_editor\config`
+/// - macOS: `$HOME/Library/Application Support/org.bevy.bevy_editor`
+fn user_config_dir() -> Result<std::path::PathBuf, PersistentError> {
+    if let Some(dir) = std::env::var_os(USER_CONFIG_DIR_ENV) {
+        return Ok(std::path::PathBuf::from(dir));
+    }
+
+    directories::ProjectDirs::from("org", "bevy", "bevy_editor")
+        .map(|dirs| dirs.config_dir().to_path_buf())
+        .ok_or(PersistentError::UserConfigDir)
 }
 
 #[inline]
-/// TODO: when the editor is an external application this should be moved to the user's configuration directory
-fn user_settings_path() -> Result<std::path::PathBuf, PersistentError> {
-    Ok(std::env::var("CARGO_MANIFEST_DIR")
-        .map(std::path::PathBuf::from)
-        .map_err(|_| PersistentError::WorkspaceConfigDirs)?
-        .join("user.toml"))
+pub(crate) fn user_settings_path() -> Result<std::path::PathBuf, PersistentError> {
+    Ok(user_config_dir()?.join("user.toml"))
+}
+
+/// Where the user settings used to be stored, next to the manifest of the project.
+fn legacy_user_settings_path() -> Option<std::path::PathBuf> {
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(|dir| std::path::PathBuf::from(dir).join("user.toml"))
+}
+
+/// Makes sure there is a user settings file at `path`.
+///
+/// An existing file at `legacy_path` is moved over, otherwise a file without any overrides is created.
+fn prepare_user_settings_file(
+    path: &std::path::Path,
+    legacy_path: Option<&std::path::Path>,
+) -> Result<(), PersistentError> {
+    if path.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|error| PersistentError::io(parent, error))?;
+    }
+
+    if let Some(legacy_path) = legacy_path.filter(|legacy_path| legacy_path.is_file()) {
+        std::fs::copy(legacy_path, path).map_err(|error| PersistentError::io(path, error))?;
+        std::fs::remove_file(legacy_path)
+            .map_err(|error| PersistentError::io(legacy_path, error))?;
+        bevy::log::info!(
+            "Moved the user settings from {} to {}",
+            legacy_path.display(),
+            path.display()
+        );
+        return Ok(());
+    }
+
+    // Leave every setting to the lower layers
+    let settings = crate::modals::user::UserSettings {
+        project_settings: crate::modals::project::ProjectSettings::default(),
+        recent_projects: Vec::new(),
+    };
+    write_user_settings(path, &settings, &bevy::utils::HashSet::new())?;
+    bevy::log::info!("Created the user settings at {}", path.display());
+    Ok(())
 }
 
 /// Save the user settings to the default location.
+///
+/// Only the project settings at `fields` are written, the others are left to the lower layers.
 pub fn save_user_settings(
     settings: &crate::modals::user::UserSettings,
+    fields: &bevy::utils::HashSet<String>,
 ) -> Result<(), PersistentError> {
     let path = user_settings_path()?;
-    let toml_string = toml::to_string(settings)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|error| PersistentError::io(parent, error))?;
+    }
 
-    std::fs::write(path, toml_string)?;
+    write_user_settings(&path, settings, fields)
+}
 
+fn write_user_settings(
+    path: &std::path::Path,
+    settings: &crate::modals::user::UserSettings,
+    fields: &bevy::utils::HashSet<String>,
+) -> Result<(), PersistentError> {
+    let toml::Value::Table(mut settings) = toml::Value::try_from(settings)? else {
+        unreachable!("the user settings are a struct");
+    };
+    if let Some(toml::Value::Table(project_settings)) = settings.get_mut("project_settings") {
+        retain_paths(project_settings, fields, "");
+    }
+
+    update_settings_file(path, |table| table.extend(settings))
+}
+
+/// Changes the settings file at `path` with `update`, creating it if it doesn't exist yet.
+///
+/// The keys `update` leaves alone are kept, like the sections of registered settings,
+/// along with the comments and formatting of the file.
+pub(crate) fn update_settings_file(
+    path: &std::path::Path,
+    update: impl FnOnce(&mut toml::Table),
+) -> Result<(), PersistentError> {
+    let (file, old) = if path.exists() {
+        read(path)?
+    } else {
+        (String::new(), toml::Table::new())
+    };
+
+    let mut table = old.clone();
+    update(&mut table);
+    table.insert(
+        crate::migration::VERSION_KEY.to_string(),
+        toml::Value::Integer(crate::SETTINGS_VERSION.into()),
+    );
+
+    write_document(path, &file, &old, &table)?;
     Ok(())
 }
 
-/// Load the user settings from the default location.
-pub fn load_user_settings() -> Result<crate::modals::user::UserSettings, PersistentError> {
+/// Removes the values of `table` that are not at one of `paths`, or on the way to one.
+fn retain_paths(table: &mut toml::Table, paths: &bevy::utils::HashSet<String>, prefix: &str) {
+    table.retain(|key, value| {
+        let path = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        if paths.contains(&path) {
+            return true;
+        }
+
+        let nested = format!("{path}.");
+        match value {
+            toml::Value::Table(table) if paths.iter().any(|p| p.starts_with(&nested)) => {
+                retain_paths(table, paths, &path);
+                true
+            }
+            _ => false,
+        }
+    });
+}
+
+/// Load the user settings from the default location, along with the table they were read from.
+pub fn load_user_settings(
+    migrations: &crate::SettingsMigrations,
+) -> Result<(crate::modals::user::UserSettings, toml::Table), PersistentError> {
     let path = user_settings_path()?;
+    prepare_user_settings_file(&path, legacy_user_settings_path().as_deref())?;
 
-    load(path)
+    load_settings_file(&path, crate::SettingsLayer::User, migrations)
 }
 
-/// Load the workspace settings from the default location.
+/// Load the workspace settings of the project at `root`, along with the table they were read from.
 pub fn load_workspace_settings(
-) -> Result<crate::modals::workspace::WorkspaceSettings, PersistentError> {
-    let path = std::env::var("CARGO_MANIFEST_DIR")
-        .map(std::path::PathBuf::from)
-        .map_err(|_| PersistentError::WorkspaceConfigDirs)?;
+    root: &std::path::Path,
+    migrations: &crate::SettingsMigrations,
+) -> Result<(crate::modals::workspace::WorkspaceSettings, toml::Table), PersistentError> {
+    load_settings_file(
+        &workspace_settings_path(root),
+        crate::SettingsLayer::Workspace,
+        migrations,
+    )
+}
 
-    load(path.join("Bevy.toml"))
+/// Save the launch profiles to the workspace settings of the project at `root`.
+pub(crate) fn save_launch_profiles(
+    root: &std::path::Path,
+    launch: &[crate::modals::launch::LaunchProfile],
+    default_launch: Option<&str>,
+) -> Result<(), PersistentError> {
+    let launch = toml::Value::try_from(launch)?;
+    update_settings_file(&workspace_settings_path(root), |table| {
+        table.insert("launch".to_string(), launch);
+        match default_launch {
+            Some(name) => table.insert(
+                "default_launch".to_string(),
+                toml::Value::String(name.to_string()),
+            ),
+            None => table.remove("default_launch"),
+        };
+    })
+}
+
+#[inline]
+pub(crate) fn workspace_settings_path(root: &std::path::Path) -> std::path::PathBuf {
+    root.join(crate::project::WORKSPACE_SETTINGS_FILE)
 }
 
 /// Errors that can occur when loading a TOML file.
 #[derive(Debug, thiserror::Error)]
 pub enum PersistentError {
+    #[error("{} does not exist", path.display())]
+    NotFound { path: std::path::PathBuf },
+    #[error("Permission denied for {}", path.display())]
+    PermissionDenied { path: std::path::PathBuf },
+    #[error("IO error for {}: {source}", path.display())]
+    FileIo {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Error parsing {}:{line}:{column}: {message}", path.display())]
+    Parse {
+        path: std::path::PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("Invalid settings in {}:{line}:{column}: {message}", path.display())]
+    Invalid {
+        path: std::path::PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
     #[error("TOML deserialization error: {0}")]
     TomlDe(#[from] toml::de::Error),
     #[error("TOML serialization error: {0}")]
     TomlSer(#[from] toml::ser::Error),
-    #[error("Error reading CARGO_MANIFEST_DIR required for workspace settings")]
+    #[error("JSON serialization error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("No project is open, the workspace settings are stored in the project")]
     WorkspaceConfigDirs,
+    #[error("No Cargo project contains {}", path.display())]
+    ProjectNotFound { path: std::path::PathBuf },
+    #[error("Settings version {found} is newer than the supported version {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+    #[error("The settings version must be a positive integer")]
+    InvalidVersion,
+    #[error("Could not find the configuration directory of the user, set {USER_CONFIG_DIR_ENV} to choose one")]
+    UserConfigDir,
+}
+
+impl PersistentError {
+    /// Adds the path of the file to an IO error.
+    pub(crate) fn io(path: &std::path::Path, error: std::io::Error) -> Self {
+        let path = path.to_path_buf();
+        match error.kind() {
+            std::io::ErrorKind::NotFound => Self::NotFound { path },
+            std::io::ErrorKind::PermissionDenied => Self::PermissionDenied { path },
+            _ => Self::FileIo {
+                path,
+                source: error,
+            },
+        }
+    }
+
+    /// Adds the path of the file and the position within `source` to an error of the TOML document.
+    fn edit(path: &std::path::Path, source: &str, error: toml_edit::TomlError) -> Self {
+        let (line, column) = position(source, error.span());
+        Self::Parse {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message: error.message().to_string(),
+        }
+    }
+
+    /// Adds the path of the file and the position within `source` to a TOML syntax error.
+    fn parse(path: &std::path::Path, source: &str, error: toml::de::Error) -> Self {
+        let Some(span) = error.span() else {
+            return Self::TomlDe(error);
+        };
+
+        let (line, column) = position(source, Some(span));
+        Self::Parse {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message: error.message().to_string(),
+        }
+    }
+
+    /// Adds the path of the file and the position within `source` to an error deserializing the settings.
+    fn invalid(path: &std::path::Path, source: &str, error: toml::de::Error) -> Self {
+        let Some(span) = error.span() else {
+            return Self::TomlDe(error);
+        };
+
+        let (line, column) = position(source, Some(span));
+        Self::Invalid {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message: error.message().to_string(),
+        }
+    }
+}
+
+/// The line and column, starting at 1, where `span` starts within `source`.
+fn position(source: &str, span: Option<std::ops::Range<usize>>) -> (usize, usize) {
+    let before = span
+        .and_then(|span| source.get(..span.start))
+        .unwrap_or(source);
+    (
+        before.matches('\n').count() + 1,
+        before.chars().rev().take_while(|c| *c != '\n').count() + 1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "bevy_editor_settings_{name}_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn first_run_creates_empty_user_settings() {
+        let dir = temp_dir("first_run");
+        let path = dir.join("config").join("user.toml");
+
+        prepare_user_settings_file(&path, None).unwrap();
+
+        let (_, table): (crate::modals::user::UserSettings, toml::Table) =
+            load_settings_file(&path, crate::SettingsLayer::User, &Default::default()).unwrap();
+        assert_eq!(
+            table["project_settings"],
+            toml::Value::Table(toml::Table::new())
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn migrates_legacy_user_settings() {
+        let dir = temp_dir("migrate");
+        let legacy_path = dir.join("user.toml");
+        let path = dir.join("config").join("user.toml");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&legacy_path, "[project_settings]\nname = \"Legacy\"\n").unwrap();
+
+        prepare_user_settings_file(&path, Some(&legacy_path)).unwrap();
+
+        assert!(!legacy_path.exists());
+        let (_, table): (crate::modals::user::UserSettings, toml::Table) =
+            load_settings_file(&path, crate::SettingsLayer::User, &Default::default()).unwrap();
+        assert_eq!(table["project_settings"]["name"].as_str(), Some("Legacy"));
+
+        // An existing file is left alone
+        std::fs::write(&legacy_path, "[project_settings]\n").unwrap();
+        prepare_user_settings_file(&path, Some(&legacy_path)).unwrap();
+        assert!(legacy_path.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn migrated_file_is_backed_up() {
+        let dir = temp_dir("versions");
+        let path = dir.join("user.toml");
+        std::fs::create_dir_all(&dir).unwrap();
+        let original =
+            "# Kept across migrations\nrecent_projects = []\n[project]\nname = \"Old\"\n";
+        std::fs::write(&path, original).unwrap();
+
+        let mut migrations = crate::SettingsMigrations::with_version(1);
+        migrations.register(crate::SettingsLayer::User, 0, |document| {
+            if let Some(settings) = document.remove("project") {
+                document.insert("project_settings".to_string(), settings);
+            }
+        });
+
+        let (_, table): (crate::modals::user::UserSettings, toml::Table) =
+            load_settings_file(&path, crate::SettingsLayer::User, &migrations).unwrap();
+        assert_eq!(table["project_settings"]["name"].as_str(), Some("Old"));
+        let backup_path = dir.join("user.toml.v0.bak");
+        assert_eq!(std::fs::read_to_string(&backup_path).unwrap(), original);
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("# Kept across migrations"));
+
+        // The migrated file is written back, and not migrated again
+        std::fs::remove_file(&backup_path).unwrap();
+        let (_, table): (crate::modals::user::UserSettings, toml::Table) =
+            load_settings_file(&path, crate::SettingsLayer::User, &migrations).unwrap();
+        assert_eq!(table["version"].as_integer(), Some(1));
+        assert!(!backup_path.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn current_file_is_not_rewritten() {
+        let dir = temp_dir("unversioned");
+        let path = dir.join("user.toml");
+        std::fs::create_dir_all(&dir).unwrap();
+        let original = "# Set by hand\n[project_settings]\nname = \"Name\"\n";
+        std::fs::write(&path, original).unwrap();
+
+        let _: (crate::modals::user::UserSettings, toml::Table) =
+            load_settings_file(&path, crate::SettingsLayer::User, &Default::default()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+        assert!(!dir.join("user.toml.v0.bak").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn saving_keeps_other_sections() {
+        let dir = temp_dir("sections");
+        let path = dir.join("user.toml");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            &path,
+            "[project_settings]\nname = \"Old\"\n# Set by hand\n[camera.editor_2d]\nsensitivity = 2.0 # Fast\n",
+        )
+        .unwrap();
+
+        let settings = crate::modals::user::UserSettings {
+            project_settings: crate::modals::project::ProjectSettings::default(),
+            recent_projects: Vec::new(),
+        };
+        write_user_settings(&path, &settings, &bevy::utils::HashSet::new()).unwrap();
+
+        let (file, table) = read(&path).unwrap();
+        assert_eq!(
+            table["project_settings"],
+            toml::Value::Table(toml::Table::new())
+        );
+        assert_eq!(
+            table["camera"]["editor_2d"]["sensitivity"].as_float(),
+            Some(2.)
+        );
+        assert!(file.contains("# Set by hand\n[camera.editor_2d]\nsensitivity = 2.0 # Fast\n"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn loading_errors() {
+        let dir = temp_dir("errors");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Bevy.toml");
+
+        assert!(matches!(
+            read(&path),
+            Err(PersistentError::NotFound { path: missing }) if missing == path
+        ));
+
+        std::fs::write(&path, "[editor_settings]\nname = \"Name\"\nbuild = =\n").unwrap();
+        assert!(matches!(
+            read(&path),
+            Err(PersistentError::Parse {
+                line: 3,
+                column: 9,
+                ..
+            })
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_settings_fall_back_to_defaults() {
+        let dir = temp_dir("invalid");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Bevy.toml");
+        std::fs::write(
+            &path,
+            format!(
+                "version = {}\n[editor_settings]\nname = 5\n[build]\ndebug = \"cargo b\"\nrelease = []\n",
+                crate::SETTINGS_VERSION
+            ),
+        )
+        .unwrap();
+
+        let (_, table): (crate::modals::workspace::WorkspaceSettings, toml::Table) =
+            load_settings_file(&path, crate::SettingsLayer::Workspace, &Default::default())
+                .unwrap();
+        assert_eq!(
+            table,
+            toml::from_str(&format!(
+                "version = {}\n[editor_settings]\n[build]\ndebug = \"cargo b\"\n",
+                crate::SETTINGS_VERSION
+            ))
+            .unwrap()
+        );
+
+        let file = std::fs::read_to_string(&path).unwrap();
+        let document = toml_edit::ImDocument::parse(file.as_str()).unwrap();
+        let keys = ["build".to_string(), "release".to_string()];
+        assert_eq!(setting_position(&file, Some(&document), &keys), (6, 11));
+
+        // Errors that keep the settings from loading point at the invalid setting
+        std::fs::write(
+            &path,
+            "# Broken\n\neditor_settings = 5\n[build]\ndebug = \"cargo b\"\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            load_settings_file::<crate::modals::workspace::WorkspaceSettings>(
+                &path,
+                crate::SettingsLayer::Workspace,
+                &Default::default()
+            ),
+            Err(PersistentError::Invalid {
+                line: 3,
+                column: 19,
+                ..
+            })
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,195 @@
+//! Finding the project the editor works on, and opening another one at runtime.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{
+    launch::LaunchProfileDiscovery,
+    modals::workspace::WorkspaceSettings,
+    persistent,
+    persistent::PersistentError,
+    watcher::{self, SettingsWatcher},
+    Settings, SettingsChanged, SettingsLayer, SettingsMigrations,
+};
+
+/// The name of the workspace settings file, next to the `Cargo.toml` of the project.
+pub const WORKSPACE_SETTINGS_FILE: &str = "Bevy.toml";
+/// How many projects are kept in the recent projects of the user settings.
+const MAX_RECENT_PROJECTS: usize = 10;
+
+/// Opens the project containing the given directory, reloading the workspace settings.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct OpenProject(pub PathBuf);
+
+/// Sent when a project was opened, with the root directory of the project.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct ProjectOpened(pub PathBuf);
+
+/// Finds the root of the project containing `start`, walking up the parent directories.
+///
+/// The closest directory with a workspace settings file next to a `Cargo.toml` is picked.
+/// For projects without settings yet, the closest `Cargo.toml` with a `[workspace]` table is picked,
+/// or the closest `Cargo.toml` if there is no workspace.
+pub fn find_project_root(start: &Path) -> Option<PathBuf> {
+    let mut workspace_dir = None;
+    let mut manifest_dir = None;
+    for dir in start.ancestors() {
+        let manifest = dir.join("Cargo.toml");
+        if !manifest.is_file() {
+            continue;
+        }
+        if dir.join(WORKSPACE_SETTINGS_FILE).is_file() {
+            return Some(dir.to_path_buf());
+        }
+        if workspace_dir.is_none() && is_workspace_manifest(&manifest) {
+            workspace_dir = Some(dir);
+        }
+        manifest_dir.get_or_insert(dir);
+    }
+    workspace_dir.or(manifest_dir).map(Path::to_path_buf)
+}
+
+/// Whether the `Cargo.toml` at `manifest` defines a workspace.
+fn is_workspace_manifest(manifest: &Path) -> bool {
+    std::fs::read_to_string(manifest)
+        .ok()
+        .and_then(|manifest| manifest.parse::<toml::Table>().ok())
+        .is_some_and(|manifest| manifest.contains_key("workspace"))
+}
+
+/// The directory the project is searched from when the editor starts.
+pub(crate) fn start_dir() -> Option<PathBuf> {
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+}
+
+/// Loads the workspace settings of the project at `root`, `None` if it has no settings file.
+pub(crate) fn load_workspace_layer(
+    root: &Path,
+    migrations: &SettingsMigrations,
+) -> Option<(WorkspaceSettings, toml::Table)> {
+    match persistent::load_workspace_settings(root, migrations) {
+        Ok(workspace_settings) => Some(workspace_settings),
+        Err(PersistentError::NotFound { .. }) => None,
+        Err(error) => {
+            error!("Error loading workspace settings: {error}");
+            None
+        }
+    }
+}
+
+/// Opens the projects of [`OpenProject`], adding them to the recent projects of the user.
+pub(crate) fn open_projects(
+    mut open_events: EventReader<OpenProject>,
+    mut settings: ResMut<Settings>,
+    mut watcher: ResMut<SettingsWatcher>,
+    migrations: Res<SettingsMigrations>,
+    mut discovery: ResMut<LaunchProfileDiscovery>,
+    mut changed_events: EventWriter<SettingsChanged>,
+    mut opened_events: EventWriter<ProjectOpened>,
+) {
+    for OpenProject(dir) in open_events.read() {
+        let Some(root) = find_project_root(dir) else {
+            error!(
+                "Error opening a project: {}",
+                PersistentError::ProjectNotFound { path: dir.clone() }
+            );
+            continue;
+        };
+        // The same project is listed once in the recent projects, however it was reached
+        let root = root.canonicalize().unwrap_or(root);
+
+        let workspace = load_workspace_layer(&root, &migrations);
+        let old_table = watcher
+            .table(SettingsLayer::Workspace)
+            .cloned()
+            .unwrap_or_default();
+        let new_table = workspace
+            .as_ref()
+            .map(|(_, table)| table.clone())
+            .unwrap_or_default();
+        watcher.watch_workspace(persistent::workspace_settings_path(&root), Some(&new_table));
+
+        settings.set_workspace_layer(workspace);
+        settings.discovered_launch_profiles.clear();
+        settings.project_root = Some(root.clone());
+        discovery.start(&settings);
+        settings.add_recent_project(root.clone());
+        match settings.save_user_settings() {
+            Ok(()) => watcher.written(SettingsLayer::User),
+            Err(error) => error!("Error saving the recent projects: {error}"),
+        }
+
+        let keys = watcher::changed_keys(&old_table, &new_table);
+        if !keys.is_empty() {
+            changed_events.send(SettingsChanged {
+                layer: SettingsLayer::Workspace,
+                keys,
+            });
+        }
+        opened_events.send(ProjectOpened(root));
+    }
+}
+
+/// Moves `root` to the front of `recent_projects`, dropping the oldest projects past the limit.
+pub(crate) fn push_recent_project(recent_projects: &mut Vec<PathBuf>, root: PathBuf) {
+    recent_projects.retain(|project| *project != root);
+    recent_projects.insert(0, root);
+    recent_projects.truncate(MAX_RECENT_PROJECTS);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_root() {
+        let dir = std::env::temp_dir().join(format!(
+            "bevy_editor_settings_project_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let member = dir.join("crates").join("game");
+        let source = member.join("src");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "").unwrap();
+        std::fs::write(member.join("Cargo.toml"), "[package]\nname = \"game\"\n").unwrap();
+
+        // Without settings the closest manifest is the root
+        assert_eq!(find_project_root(&source), Some(member.clone()));
+
+        // Unless a workspace contains it
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        assert_eq!(find_project_root(&source), Some(dir.clone()));
+
+        // The settings file next to a manifest wins
+        std::fs::write(member.join(WORKSPACE_SETTINGS_FILE), "").unwrap();
+        assert_eq!(find_project_root(&source), Some(member.clone()));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn recent_projects() {
+        let mut recent_projects = Vec::new();
+        for index in 0..=MAX_RECENT_PROJECTS {
+            push_recent_project(&mut recent_projects, PathBuf::from(format!("{index}")));
+        }
+        assert_eq!(recent_projects.len(), MAX_RECENT_PROJECTS);
+        assert_eq!(
+            recent_projects[0],
+            PathBuf::from(format!("{MAX_RECENT_PROJECTS}"))
+        );
+
+        // Opening a project again moves it to the front
+        push_recent_project(&mut recent_projects, PathBuf::from("5"));
+        assert_eq!(recent_projects[0], PathBuf::from("5"));
+        assert_eq!(recent_projects.len(), MAX_RECENT_PROJECTS);
+    }
+}
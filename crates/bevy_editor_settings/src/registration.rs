@@ -0,0 +1,439 @@
+//! Settings registered by plugins, each stored in its own section of the settings files.
+
+use std::marker::PhantomData;
+
+use bevy::{
+    prelude::*,
+    reflect::{
+        serde::{TypedReflectDeserializer, TypedReflectSerializer},
+        GetTypeRegistration, ReflectMut, ReflectRef, TypeRegistry,
+    },
+};
+use serde::de::DeserializeSeed;
+
+use crate::{
+    keymap::Keymap,
+    persistent,
+    watcher::{self, SettingsWatcher},
+    MergeError, SettingsChanged, SettingsLayer,
+};
+
+/// Extension trait to register the settings of plugins on the [`App`].
+pub trait SettingsAppExt {
+    /// Registers `T` as settings stored under `key` (like `"camera.editor_2d"`) in the settings files.
+    ///
+    /// `T` is inserted as a resource, merged field by field from its defaults, the workspace settings
+    /// and the user settings. Changes to the resource are written back to the user settings,
+    /// changes to the files are loaded into the resource.
+    ///
+    /// The [`EditorSettingsPlugin`](crate::EditorSettingsPlugin) has to be added first.
+    fn register_settings<T>(&mut self, key: impl Into<String>) -> &mut Self
+    where
+        T: Resource + Reflect + Default + GetTypeRegistration;
+
+    /// Registers the action `action` (like `"pane.close"`) with its default key bindings
+    /// (like `["Ctrl+W"]`), sending [`ActionTriggered`](crate::ActionTriggered) when pressed.
+    ///
+    /// The bindings can be changed in the `[keymap]` section of the settings files,
+    /// which is loaded at startup once every action is registered.
+    /// The [`EditorSettingsPlugin`](crate::EditorSettingsPlugin) has to be added first.
+    fn register_action(
+        &mut self,
+        action: impl Into<String>,
+        default_bindings: &[&str],
+    ) -> &mut Self;
+}
+
+impl SettingsAppExt for App {
+    fn register_action(
+        &mut self,
+        action: impl Into<String>,
+        default_bindings: &[&str],
+    ) -> &mut Self {
+        let action = action.into();
+        let bindings = default_bindings
+            .iter()
+            .filter_map(|binding| {
+                binding
+                    .parse()
+                    .inspect_err(|error| {
+                        error!("Invalid default key binding {binding} of {action}: {error}");
+                    })
+                    .ok()
+            })
+            .collect();
+
+        self.world_mut()
+            .resource_mut::<Keymap>()
+            .set_default(action, bindings);
+        self
+    }
+
+    fn register_settings<T>(&mut self, key: impl Into<String>) -> &mut Self
+    where
+        T: Resource + Reflect + Default + GetTypeRegistration,
+    {
+        let key = key.into();
+        self.register_type::<T>();
+        #[cfg(feature = "schema")]
+        self.world_mut()
+            .get_resource_or_init::<SettingsSections>()
+            .0
+            .push((key.clone(), std::any::TypeId::of::<T>()));
+
+        let (value, user_section) = {
+            let watcher = self
+                .world()
+                .get_resource::<SettingsWatcher>()
+                .expect("the `EditorSettingsPlugin` has to be added before registering settings");
+            let registry = self.world().resource::<AppTypeRegistry>().read();
+            load_section::<T>(&registry, &key, watcher)
+        };
+
+        self.insert_resource(value)
+            .insert_resource(RegisteredSettings::<T> {
+                key,
+                user_section,
+                marker: PhantomData,
+            })
+            .add_systems(
+                Update,
+                (reload_settings::<T>, save_settings::<T>)
+                    .chain()
+                    .after(watcher::reload_changed_settings),
+            )
+    }
+}
+
+/// Where the settings `T` are stored.
+#[derive(Resource)]
+struct RegisteredSettings<T> {
+    key: String,
+    /// The fields the user settings override, as they are in the file.
+    user_section: toml::Table,
+    marker: PhantomData<fn() -> T>,
+}
+
+/// The sections of the registered settings, to describe them in the JSON Schemas of the settings files.
+#[cfg(feature = "schema")]
+#[derive(Resource, Default)]
+pub(crate) struct SettingsSections(pub(crate) Vec<(String, std::any::TypeId)>);
+
+/// Loads the settings again when their section of a settings file changed.
+fn reload_settings<T: Resource + Reflect + Default>(
+    mut events: EventReader<SettingsChanged>,
+    watcher: Res<SettingsWatcher>,
+    registry: Res<AppTypeRegistry>,
+    mut registration: ResMut<RegisteredSettings<T>>,
+    mut settings: ResMut<T>,
+) {
+    let prefix = format!("{}.", registration.key);
+    let changed = events
+        .read()
+        .any(|event| event.keys.iter().any(|key| key.starts_with(&prefix)));
+    // The events after the first match don't change anything
+    events.clear();
+    if !changed {
+        return;
+    }
+
+    let registry = registry.read();
+    let (value, user_section) = load_section::<T>(&registry, &registration.key, &watcher);
+    registration.user_section = user_section;
+    if value.reflect_partial_eq(settings.as_partial_reflect()) != Some(true) {
+        *settings = value;
+    }
+}
+
+/// Writes the fields that differ from the lower layers to the user settings when the settings change.
+fn save_settings<T: Resource + Reflect + Default>(
+    settings: Res<T>,
+    mut watcher: ResMut<SettingsWatcher>,
+    registry: Res<AppTypeRegistry>,
+    mut registration: ResMut<RegisteredSettings<T>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let registry = registry.read();
+    let base = layered::<T>(
+        &registry,
+        &registration.key,
+        &[watcher.table(SettingsLayer::Workspace)],
+    );
+    let user_section = changed_fields(
+        settings.as_partial_reflect(),
+        base.as_partial_reflect(),
+        &registry,
+    );
+    if user_section == registration.user_section {
+        return;
+    }
+
+    let result = persistent::user_settings_path().and_then(|path| {
+        persistent::update_settings_file(&path, |table| {
+            set_section(table, &registration.key, user_section.clone());
+        })
+    });
+    match result {
+        Ok(()) => {
+            registration.user_section = user_section;
+            watcher.written(SettingsLayer::User);
+        }
+        Err(error) => error!("Error saving the {} settings: {error}", registration.key),
+    }
+}
+
+/// Merges the settings under `key` from the loaded files, along with the fields the user settings override.
+fn load_section<T: Reflect + Default>(
+    registry: &TypeRegistry,
+    key: &str,
+    watcher: &SettingsWatcher,
+) -> (T, toml::Table) {
+    let workspace = watcher.table(SettingsLayer::Workspace);
+    let user = watcher.table(SettingsLayer::User);
+
+    let base = layered::<T>(registry, key, &[workspace]);
+    let value = layered::<T>(registry, key, &[workspace, user]);
+    let user_section = changed_fields(
+        value.as_partial_reflect(),
+        base.as_partial_reflect(),
+        registry,
+    );
+    (value, user_section)
+}
+
+/// Creates the settings from their defaults and the sections under `key` of `layers`, lowest first.
+fn layered<T: Reflect + Default>(
+    registry: &TypeRegistry,
+    key: &str,
+    layers: &[Option<&toml::Table>],
+) -> T {
+    let mut value = T::default();
+    for table in layers.iter().flatten() {
+        if let Some(section) = section(table, key) {
+            apply_table(value.as_partial_reflect_mut(), section, registry, key);
+        }
+    }
+    value
+}
+
+/// Finds the table under the dotted `key` in `table`.
+fn section<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Table> {
+    key.split('.')
+        .try_fold(table, |table, part| match table.get(part) {
+            Some(toml::Value::Table(table)) => Some(table),
+            _ => None,
+        })
+}
+
+/// Replaces the table under the dotted `key` in `table`, removing it when `section` is empty.
+fn set_section(table: &mut toml::Table, key: &str, section: toml::Table) {
+    let Some((first, rest)) = key.split_once('.') else {
+        if section.is_empty() {
+            table.remove(key);
+        } else {
+            table.insert(key.to_string(), toml::Value::Table(section));
+        }
+        return;
+    };
+
+    let entry = table
+        .entry(first)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if !entry.is_table() {
+        *entry = toml::Value::Table(toml::Table::new());
+    }
+    if let toml::Value::Table(child) = entry {
+        set_section(child, rest, section);
+        if child.is_empty() {
+            table.remove(first);
+        }
+    }
+}
+
+/// Sets the fields of `value` that are set in `table`, following nested structs.
+///
+/// Settings that are unknown or invalid are skipped with a warning.
+fn apply_table(
+    value: &mut dyn PartialReflect,
+    table: &toml::Table,
+    registry: &TypeRegistry,
+    prefix: &str,
+) {
+    let ReflectMut::Struct(value) = value.reflect_mut() else {
+        return;
+    };
+
+    for (key, item) in table {
+        let path = format!("{prefix}.{key}");
+        let Some(field) = value.field_mut(key) else {
+            warn!("Unknown setting {path}");
+            continue;
+        };
+
+        match item {
+            toml::Value::Table(table) if matches!(field.reflect_ref(), ReflectRef::Struct(_)) => {
+                apply_table(field, table, registry, &path);
+            }
+            _ => {
+                if let Err(error) = apply_value(field, item, registry) {
+                    warn!("Invalid setting {path}: {error}");
+                }
+            }
+        }
+    }
+}
+
+/// Deserializes `item` as the type of `field` and applies it.
+fn apply_value(
+    field: &mut dyn PartialReflect,
+    item: &toml::Value,
+    registry: &TypeRegistry,
+) -> Result<(), MergeError> {
+    let registration = field
+        .get_represented_type_info()
+        .and_then(|info| registry.get(info.type_id()))
+        .ok_or_else(|| MergeError::Unregistered(field.reflect_type_path().to_string()))?;
+    let value = TypedReflectDeserializer::new(registration, registry).deserialize(item.clone())?;
+    field.try_apply(value.as_ref())?;
+    Ok(())
+}
+
+/// Collects the fields of `value` that differ from `base`, as they are written to the settings files.
+fn changed_fields(
+    value: &dyn PartialReflect,
+    base: &dyn PartialReflect,
+    registry: &TypeRegistry,
+) -> toml::Table {
+    let mut table = toml::Table::new();
+    let (ReflectRef::Struct(value), ReflectRef::Struct(base)) =
+        (value.reflect_ref(), base.reflect_ref())
+    else {
+        return table;
+    };
+
+    for (index, field) in value.iter_fields().enumerate() {
+        let (Some(name), Some(base_field)) = (value.name_at(index), base.field_at(index)) else {
+            continue;
+        };
+
+        if matches!(field.reflect_ref(), ReflectRef::Struct(_)) {
+            let fields = changed_fields(field, base_field, registry);
+            if !fields.is_empty() {
+                table.insert(name.to_string(), toml::Value::Table(fields));
+            }
+        } else if field.reflect_partial_eq(base_field) != Some(true) {
+            match toml::Value::try_from(TypedReflectSerializer::new(field, registry)) {
+                Ok(item) => {
+                    table.insert(name.to_string(), item);
+                }
+                Err(error) => warn!("Could not write the setting {name}: {error}"),
+            }
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Reflect, Debug, PartialEq)]
+    struct CameraSettings {
+        sensitivity: f32,
+        invert_y: bool,
+        zoom: ZoomLimits,
+    }
+
+    #[derive(Reflect, Debug, PartialEq)]
+    struct ZoomLimits {
+        min: f32,
+        max: f32,
+    }
+
+    impl Default for CameraSettings {
+        fn default() -> Self {
+            Self {
+                sensitivity: 1.,
+                invert_y: false,
+                zoom: ZoomLimits { min: 0.1, max: 10. },
+            }
+        }
+    }
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::default();
+        registry.register::<CameraSettings>();
+        registry
+    }
+
+    #[test]
+    fn layered_sections() {
+        let registry = registry();
+        let workspace: toml::Table = toml::from_str(
+            r#"
+            [camera.editor_2d]
+            sensitivity = 2.0
+            zoom.max = 20.0
+            "#,
+        )
+        .unwrap();
+        let user: toml::Table = toml::from_str(
+            r#"
+            [camera.editor_2d]
+            invert_y = true
+            zoom.max = 5.0
+            # Invalid and unknown settings are skipped
+            sensitivity = "fast"
+            speed = 3.0
+            "#,
+        )
+        .unwrap();
+
+        let value = layered::<CameraSettings>(
+            &registry,
+            "camera.editor_2d",
+            &[Some(&workspace), Some(&user)],
+        );
+        assert_eq!(
+            value,
+            CameraSettings {
+                sensitivity: 2.,
+                invert_y: true,
+                zoom: ZoomLimits { min: 0.1, max: 5. },
+            }
+        );
+
+        // Only the fields that differ from the lower layers are written back
+        let base = layered::<CameraSettings>(&registry, "camera.editor_2d", &[Some(&workspace)]);
+        let fields = changed_fields(&value, &base, &registry);
+        assert_eq!(
+            fields,
+            toml::from_str("invert_y = true\nzoom.max = 5.0").unwrap()
+        );
+    }
+
+    #[test]
+    fn set_nested_section() {
+        let mut table: toml::Table = toml::from_str("[project_settings]\nname = \"Name\"").unwrap();
+
+        set_section(
+            &mut table,
+            "camera.editor_2d",
+            toml::from_str("sensitivity = 2.0").unwrap(),
+        );
+        assert_eq!(
+            section(&table, "camera.editor_2d").unwrap()["sensitivity"].as_float(),
+            Some(2.)
+        );
+
+        // Empty sections are removed along with their empty parents
+        set_section(&mut table, "camera.editor_2d", toml::Table::new());
+        assert_eq!(
+            table,
+            toml::from_str("[project_settings]\nname = \"Name\"").unwrap()
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! JSON Schemas of the settings files, for autocompletion and validation in editors.
+
+use std::any::TypeId;
+
+use bevy::{
+    prelude::*,
+    reflect::{TypeInfo, VariantInfo},
+};
+use schemars::{
+    schema::{InstanceType, RootSchema, Schema, SchemaObject},
+    schema_for,
+};
+
+use crate::{
+    migration::VERSION_KEY,
+    modals::{user::UserSettings, workspace::WorkspaceSettings},
+    persistent::PersistentError,
+    registration::SettingsSections,
+};
+
+/// The JSON Schemas of the workspace and user settings files.
+///
+/// Point a TOML language server like taplo at them to get autocompletion and validation,
+/// for example with a `#:schema ./bevy.schema.json` comment at the top of a settings file.
+pub struct SettingsSchemas {
+    /// The schema of the workspace settings in `Bevy.toml`.
+    pub workspace: RootSchema,
+    /// The schema of the user settings in `user.toml`.
+    pub user: RootSchema,
+}
+
+impl SettingsSchemas {
+    /// Generates the schemas, including the sections of the settings registered in `world`
+    /// with [`register_settings`](crate::SettingsAppExt::register_settings).
+    pub fn new(world: &World) -> Self {
+        let mut schemas = Self {
+            workspace: schema_for!(WorkspaceSettings),
+            user: schema_for!(UserSettings),
+        };
+
+        let version = SchemaObject {
+            instance_type: Some(InstanceType::Integer.into()),
+            ..default()
+        };
+        for root in [&mut schemas.workspace, &mut schemas.user] {
+            root.schema
+                .object()
+                .properties
+                .insert(VERSION_KEY.to_string(), Schema::Object(version.clone()));
+        }
+
+        let (Some(sections), Some(registry)) = (
+            world.get_resource::<SettingsSections>(),
+            world.get_resource::<AppTypeRegistry>(),
+        ) else {
+            return schemas;
+        };
+        let registry = registry.read();
+        for (key, type_id) in &sections.0 {
+            let section = registry
+                .get_type_info(*type_id)
+                .map_or(Schema::Bool(true), reflect_schema);
+            for root in [&mut schemas.workspace, &mut schemas.user] {
+                insert_section(&mut root.schema, key, section.clone());
+            }
+        }
+
+        schemas
+    }
+
+    /// Writes the schemas to `bevy.schema.json` and `user.schema.json` in `dir`.
+    pub fn write(&self, dir: impl AsRef<std::path::Path>) -> Result<(), PersistentError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir).map_err(|error| PersistentError::io(dir, error))?;
+
+        for (name, schema) in [
+            ("bevy.schema.json", &self.workspace),
+            ("user.schema.json", &self.user),
+        ] {
+            let path = dir.join(name);
+            std::fs::write(&path, serde_json::to_string_pretty(schema)?)
+                .map_err(|error| PersistentError::io(&path, error))?;
+        }
+        Ok(())
+    }
+}
+
+/// Places the schema of a section under the dotted `key` of `schema`.
+fn insert_section(schema: &mut SchemaObject, key: &str, section: Schema) {
+    let properties = &mut schema.object().properties;
+    let Some((first, rest)) = key.split_once('.') else {
+        properties.insert(key.to_string(), section);
+        return;
+    };
+
+    let entry = properties
+        .entry(first.to_string())
+        .or_insert_with(|| Schema::Object(object_schema()));
+    if let Schema::Bool(_) = entry {
+        *entry = Schema::Object(object_schema());
+    }
+    if let Schema::Object(child) = entry {
+        insert_section(child, rest, section);
+    }
+}
+
+fn object_schema() -> SchemaObject {
+    SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        ..default()
+    }
+}
+
+/// Describes how a reflected type is written to the settings files.
+///
+/// Types that can't be described accept any value.
+fn reflect_schema(info: &TypeInfo) -> Schema {
+    let mut schema = SchemaObject::default();
+    match info {
+        TypeInfo::Struct(info) => {
+            schema = object_schema();
+            let object = schema.object();
+            for field in info.iter() {
+                let field_schema = field.type_info().map_or(Schema::Bool(true), reflect_schema);
+                object
+                    .properties
+                    .insert(field.name().to_string(), field_schema);
+            }
+            object.additional_properties = Some(Box::new(Schema::Bool(false)));
+        }
+        TypeInfo::List(info) => {
+            schema.instance_type = Some(InstanceType::Array.into());
+            schema.array().items = info.item_info().map(|item| reflect_schema(item).into());
+        }
+        TypeInfo::Array(info) => {
+            schema.instance_type = Some(InstanceType::Array.into());
+            let array = schema.array();
+            array.items = info.item_info().map(|item| reflect_schema(item).into());
+            array.min_items = u32::try_from(info.capacity()).ok();
+            array.max_items = array.min_items;
+        }
+        TypeInfo::Map(info) => {
+            schema = object_schema();
+            schema.object().additional_properties = info
+                .value_info()
+                .map(|value| Box::new(reflect_schema(value)));
+        }
+        // Unit variants are written as their name
+        TypeInfo::Enum(info)
+            if info
+                .iter()
+                .all(|variant| matches!(variant, VariantInfo::Unit(_))) =>
+        {
+            schema.instance_type = Some(InstanceType::String.into());
+            schema.enum_values = Some(
+                info.variant_names()
+                    .iter()
+                    .map(|name| (*name).into())
+                    .collect(),
+            );
+        }
+        TypeInfo::Opaque(info) => match primitive_type(info.type_id()) {
+            Some(instance_type) => schema.instance_type = Some(instance_type.into()),
+            None => return Schema::Bool(true),
+        },
+        _ => return Schema::Bool(true),
+    }
+    Schema::Object(schema)
+}
+
+/// The JSON type of a primitive.
+fn primitive_type(type_id: TypeId) -> Option<InstanceType> {
+    let is_any = |types: &[TypeId]| types.contains(&type_id);
+
+    if is_any(&[TypeId::of::<bool>()]) {
+        Some(InstanceType::Boolean)
+    } else if is_any(&[TypeId::of::<String>(), TypeId::of::<char>()]) {
+        Some(InstanceType::String)
+    } else if is_any(&[TypeId::of::<f32>(), TypeId::of::<f64>()]) {
+        Some(InstanceType::Number)
+    } else if is_any(&[
+        TypeId::of::<i8>(),
+        TypeId::of::<i16>(),
+        TypeId::of::<i32>(),
+        TypeId::of::<i64>(),
+        TypeId::of::<isize>(),
+        TypeId::of::<u8>(),
+        TypeId::of::<u16>(),
+        TypeId::of::<u32>(),
+        TypeId::of::<u64>(),
+        TypeId::of::<usize>(),
+    ]) {
+        Some(InstanceType::Integer)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::Typed;
+
+    use super::*;
+
+    #[derive(Reflect)]
+    struct CameraSettings {
+        sensitivity: f32,
+        invert_y: bool,
+        mode: CameraMode,
+    }
+
+    #[derive(Reflect)]
+    enum CameraMode {
+        Orbit,
+        Fly,
+    }
+
+    #[test]
+    fn schema_of_sections() {
+        let mut app = App::new();
+        app.register_type::<CameraSettings>()
+            .insert_resource(SettingsSections(vec![(
+                "camera.editor_2d".to_string(),
+                TypeId::of::<CameraSettings>(),
+            )]));
+
+        let schemas = SettingsSchemas::new(app.world());
+        let schema = serde_json::to_value(&schemas.user.schema).unwrap();
+        let section = &schema["properties"]["camera"]["properties"]["editor_2d"];
+        assert_eq!(
+            section["properties"]["sensitivity"]["type"].as_str(),
+            Some("number")
+        );
+        assert_eq!(
+            section["properties"]["mode"]["enum"],
+            serde_json::json!(["Orbit", "Fly"])
+        );
+        assert_eq!(
+            schema["properties"][VERSION_KEY]["type"].as_str(),
+            Some("integer")
+        );
+        assert!(schema["properties"]["project_settings"].is_object());
+
+        // Types that can't be described accept anything
+        assert_eq!(
+            reflect_schema(<Option<f32> as Typed>::type_info()),
+            Schema::Bool(true)
+        );
+    }
+}
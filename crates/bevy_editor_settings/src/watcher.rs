@@ -0,0 +1,339 @@
+//! Reloading the settings files when they change on disk.
+
+use std::{collections::BTreeMap, path::PathBuf, time::SystemTime};
+
+use bevy::prelude::*;
+
+use crate::{migration::VERSION_KEY, persistent, Settings, SettingsLayer, SettingsMigrations};
+
+/// How often the settings files are checked for changes, in seconds.
+const POLL_INTERVAL: f32 = 1.;
+
+/// Sent when a settings file changed on disk and the [`Settings`] were reloaded.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct SettingsChanged {
+    /// The layer that was reloaded.
+    pub layer: SettingsLayer,
+    /// The dotted paths of the keys that changed in the file, like `project_settings.name`.
+    pub keys: Vec<String>,
+}
+
+/// Keeps track of the settings files, to notice when they change.
+#[derive(Resource)]
+pub(crate) struct SettingsWatcher {
+    timer: Timer,
+    files: Vec<WatchedFile>,
+}
+
+struct WatchedFile {
+    layer: SettingsLayer,
+    path: PathBuf,
+    /// When the file was last modified, `None` if it doesn't exist.
+    modified: Option<SystemTime>,
+    /// The contents of the file when it was last loaded.
+    table: toml::Table,
+}
+
+impl WatchedFile {
+    fn new(layer: SettingsLayer, path: PathBuf, table: Option<&toml::Table>) -> Self {
+        Self {
+            layer,
+            modified: modified_time(&path),
+            path,
+            table: table.cloned().unwrap_or_default(),
+        }
+    }
+}
+
+fn modified_time(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+impl SettingsWatcher {
+    /// Watches the workspace settings file at `workspace_path` and the user settings file,
+    /// starting from the tables that were loaded.
+    pub(crate) fn new(
+        workspace_path: Option<PathBuf>,
+        workspace: Option<&toml::Table>,
+        user: Option<&toml::Table>,
+    ) -> Self {
+        let mut files = Vec::new();
+        if let Some(path) = workspace_path {
+            files.push(WatchedFile::new(SettingsLayer::Workspace, path, workspace));
+        }
+        if let Ok(path) = persistent::user_settings_path() {
+            files.push(WatchedFile::new(SettingsLayer::User, path, user));
+        }
+
+        Self {
+            timer: Timer::from_seconds(POLL_INTERVAL, TimerMode::Repeating),
+            files,
+        }
+    }
+
+    /// Watches the workspace settings file at `path` instead, starting from the table that was loaded.
+    pub(crate) fn watch_workspace(&mut self, path: PathBuf, workspace: Option<&toml::Table>) {
+        self.files
+            .retain(|file| file.layer != SettingsLayer::Workspace);
+        self.files
+            .push(WatchedFile::new(SettingsLayer::Workspace, path, workspace));
+    }
+
+    /// The contents of the file of `layer` when it was last loaded, empty if there is no such file.
+    pub(crate) fn table(&self, layer: SettingsLayer) -> Option<&toml::Table> {
+        self.files
+            .iter()
+            .find(|file| file.layer == layer)
+            .map(|file| &file.table)
+    }
+
+    /// Catches up with a write of the editor itself to the file of `layer`, so that only the changes
+    /// made by others are reloaded.
+    pub(crate) fn written(&mut self, layer: SettingsLayer) {
+        for file in self.files.iter_mut().filter(|file| file.layer == layer) {
+            file.modified = modified_time(&file.path);
+            if let Ok((_, table)) = persistent::read(&file.path) {
+                file.table = table;
+            }
+        }
+    }
+
+    /// Reloads the files that changed since the last poll into `settings`.
+    fn poll(
+        &mut self,
+        settings: &mut Settings,
+        migrations: &SettingsMigrations,
+    ) -> Vec<SettingsChanged> {
+        let mut changes = Vec::new();
+
+        for file in &mut self.files {
+            let modified = modified_time(&file.path);
+            if modified == file.modified {
+                continue;
+            }
+            file.modified = modified;
+
+            // A removed file no longer sets anything
+            let table = match (modified.is_some(), file.layer) {
+                (false, SettingsLayer::Workspace) => {
+                    settings.set_workspace_layer(None);
+                    Ok(toml::Table::new())
+                }
+                (false, _) => {
+                    settings.set_user_layer(None);
+                    Ok(toml::Table::new())
+                }
+                (true, SettingsLayer::Workspace) => persistent::load_settings_file(
+                    &file.path, file.layer, migrations,
+                )
+                .map(|layer| {
+                    let table = layer.1.clone();
+                    settings.set_workspace_layer(Some(layer));
+                    table
+                }),
+                (true, _) => persistent::load_settings_file(&file.path, file.layer, migrations)
+                    .map(|layer| {
+                        let table = layer.1.clone();
+                        settings.set_user_layer(Some(layer));
+                        table
+                    }),
+            };
+
+            // Keep the previous settings while the file is invalid, it is probably being edited
+            let table = match table {
+                Ok(table) => table,
+                Err(error) => {
+                    error!("Error reloading {}: {error}", file.path.display());
+                    continue;
+                }
+            };
+
+            // Migrating the file writes it back
+            file.modified = modified_time(&file.path);
+            let keys = changed_keys(&file.table, &table);
+            file.table = table;
+            if !keys.is_empty() {
+                changes.push(SettingsChanged {
+                    layer: file.layer,
+                    keys,
+                });
+            }
+        }
+
+        changes
+    }
+}
+
+/// Checks the settings files for changes, reloading the [`Settings`] and sending [`SettingsChanged`].
+pub(crate) fn reload_changed_settings(
+    time: Res<Time<Real>>,
+    mut watcher: ResMut<SettingsWatcher>,
+    mut settings: ResMut<Settings>,
+    migrations: Res<SettingsMigrations>,
+    mut events: EventWriter<SettingsChanged>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let changes = watcher.poll(settings.bypass_change_detection(), &migrations);
+    if !changes.is_empty() {
+        settings.set_changed();
+        events.send_batch(changes);
+    }
+}
+
+/// Lists the dotted paths of the settings that differ between two tables.
+pub(crate) fn changed_keys(old: &toml::Table, new: &toml::Table) -> Vec<String> {
+    let mut old_values = BTreeMap::new();
+    let mut new_values = BTreeMap::new();
+    flatten(old, "", &mut old_values);
+    flatten(new, "", &mut new_values);
+
+    let mut keys: Vec<String> = old_values
+        .keys()
+        .chain(new_values.keys())
+        // The version of the file is not a setting
+        .filter(|key| key.as_str() != VERSION_KEY)
+        .filter(|key| old_values.get(*key) != new_values.get(*key))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+fn flatten<'a>(
+    table: &'a toml::Table,
+    prefix: &str,
+    values: &mut BTreeMap<String, &'a toml::Value>,
+) {
+    for (key, value) in table {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            toml::Value::Table(table) => flatten(table, &path, values),
+            _ => {
+                values.insert(path, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{modals::project::ProjectSettings, SETTINGS_VERSION};
+
+    #[test]
+    fn changed_keys_of_tables() {
+        let old: toml::Table = toml::from_str(
+            r#"
+            [project_settings]
+            name = "Old"
+            [build]
+            debug = "cargo build"
+            "#,
+        )
+        .unwrap();
+        let new: toml::Table = toml::from_str(
+            r#"
+            [project_settings]
+            name = "New"
+            [build]
+            debug = "cargo build"
+            release = "cargo build --release"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            changed_keys(&old, &new),
+            ["build.release", "project_settings.name"]
+        );
+        assert!(changed_keys(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn reload_user_settings() {
+        let dir = std::env::temp_dir().join(format!(
+            "bevy_editor_settings_watcher_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("user.toml");
+        let _ = std::fs::remove_file(&path);
+
+        let mut settings = Settings::from_layers(None, None, ProjectSettings::default());
+        let migrations = SettingsMigrations::default();
+        let mut watcher = SettingsWatcher {
+            timer: Timer::from_seconds(POLL_INTERVAL, TimerMode::Repeating),
+            files: vec![WatchedFile::new(SettingsLayer::User, path.clone(), None)],
+        };
+        assert!(watcher.poll(&mut settings, &migrations).is_empty());
+
+        std::fs::write(
+            &path,
+            format!("version = {SETTINGS_VERSION}\n[project_settings]\nname = \"Live\"\n"),
+        )
+        .unwrap();
+        assert_eq!(
+            watcher.poll(&mut settings, &migrations),
+            [SettingsChanged {
+                layer: SettingsLayer::User,
+                keys: vec!["project_settings.name".to_string()],
+            }]
+        );
+        assert_eq!(settings.setting_source("name"), Some(SettingsLayer::User));
+
+        // Removing the file drops the layer
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.poll(&mut settings, &migrations).len(), 1);
+        assert_eq!(settings.user_settings(), None);
+        assert_eq!(
+            settings.setting_source("name"),
+            Some(SettingsLayer::Project)
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn own_writes_are_not_reloaded() {
+        let dir = std::env::temp_dir().join(format!(
+            "bevy_editor_settings_watcher_own_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("user.toml");
+        let _ = std::fs::remove_file(&path);
+
+        let mut settings = Settings::from_layers(None, None, ProjectSettings::default());
+        let migrations = SettingsMigrations::default();
+        let mut watcher = SettingsWatcher {
+            timer: Timer::from_seconds(POLL_INTERVAL, TimerMode::Repeating),
+            files: vec![WatchedFile::new(SettingsLayer::User, path.clone(), None)],
+        };
+
+        persistent::update_settings_file(&path, |table| {
+            table.insert(
+                "theme".to_string(),
+                toml::Value::String("light".to_string()),
+            );
+        })
+        .unwrap();
+        watcher.written(SettingsLayer::User);
+        assert!(watcher.poll(&mut settings, &migrations).is_empty());
+        assert_eq!(
+            watcher.table(SettingsLayer::User).unwrap()["theme"].as_str(),
+            Some("light")
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
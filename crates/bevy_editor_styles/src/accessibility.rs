@@ -0,0 +1,288 @@
+//! Simulating color blindness on the [`Theme`] and checking the contrast of its colors.
+
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::Theme;
+
+/// The contrast text needs with its background, WCAG 2 level AA.
+pub const TEXT_CONTRAST: f32 = 4.5;
+/// The contrast other meaningful colors, like focus rings and status colors, need with their background.
+pub const NON_TEXT_CONTRAST: f32 = 3.;
+
+/// A color vision deficiency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorBlindness {
+    /// Missing green cones, red and green are hard to tell apart.
+    Deuteranopia,
+    /// Missing red cones, red and green are hard to tell apart and reds look darker.
+    Protanopia,
+    /// Missing blue cones, blue and green, and yellow and red are hard to tell apart.
+    Tritanopia,
+}
+
+impl ColorBlindness {
+    /// The transform of linear sRGB, from Machado, Oliveira and Fernandes (2009) at full severity.
+    fn matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            Self::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Self::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Self::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+
+    /// How `color` looks with this deficiency, kept in the OKLCH color space.
+    pub fn simulate(self, color: Color) -> Color {
+        let linear = LinearRgba::from(color);
+        let rgb = [linear.red, linear.green, linear.blue];
+        let [red, green, blue] = self
+            .matrix()
+            .map(|row| (0..3).map(|i| row[i] * rgb[i]).sum::<f32>().clamp(0., 1.));
+        Oklcha::from(LinearRgba::new(red, green, blue, linear.alpha)).into()
+    }
+}
+
+/// Shows the [`Theme`] as seen with a color vision deficiency, to check that the editor stays usable.
+///
+/// The simulation is applied whenever the theme is switched with [`SetTheme`](crate::SetTheme),
+/// and to the current theme when this resource changes.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimulatedColorBlindness(pub Option<ColorBlindness>);
+
+/// The contrast ratio between two colors according to WCAG 2, from 1 to 21.
+///
+/// The alpha of the colors is ignored.
+pub fn contrast_ratio(a: Color, b: Color) -> f32 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// The relative luminance of a color according to WCAG 2, from 0 for black to 1 for white.
+fn relative_luminance(color: Color) -> f32 {
+    let color = LinearRgba::from(color);
+    0.2126 * color.red.clamp(0., 1.)
+        + 0.7152 * color.green.clamp(0., 1.)
+        + 0.0722 * color.blue.clamp(0., 1.)
+}
+
+/// A pair of theme colors without enough contrast, found by [`Theme::contrast_failures`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastFailure {
+    /// The token shown in front, like `text.primary`.
+    pub foreground: &'static str,
+    /// The token it is shown on, like `surface.panel`.
+    pub background: &'static str,
+    /// The contrast ratio of the colors.
+    pub ratio: f32,
+    /// The contrast ratio the pair needs.
+    pub required: f32,
+}
+
+impl fmt::Display for ContrastFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} on {} has a contrast of {:.2}:1, {}:1 is required",
+            self.foreground, self.background, self.ratio, self.required
+        )
+    }
+}
+
+impl Theme {
+    /// A copy of the theme as seen with the given color vision deficiency.
+    pub fn simulate_color_blindness(&self, deficiency: ColorBlindness) -> Theme {
+        let mut theme = self.clone();
+        for color in theme.colors.colors_mut() {
+            *color = deficiency.simulate(*color);
+        }
+        theme
+    }
+
+    /// Checks that the text and other meaningful colors stand out from the surfaces they are shown on.
+    pub fn contrast_failures(&self) -> Vec<ContrastFailure> {
+        let colors = &self.colors;
+        let surface = &colors.surface;
+        let pairs = [
+            (
+                ("text.primary", colors.text.primary),
+                ("surface.background", surface.background),
+                TEXT_CONTRAST,
+            ),
+            (
+                ("text.primary", colors.text.primary),
+                ("surface.panel", surface.panel),
+                TEXT_CONTRAST,
+            ),
+            (
+                ("text.primary", colors.text.primary),
+                ("surface.header", surface.header),
+                TEXT_CONTRAST,
+            ),
+            (
+                ("text.primary", colors.text.primary),
+                ("surface.raised", surface.raised),
+                TEXT_CONTRAST,
+            ),
+            (
+                ("text.secondary", colors.text.secondary),
+                ("surface.panel", surface.panel),
+                TEXT_CONTRAST,
+            ),
+            (
+                ("text.secondary", colors.text.secondary),
+                ("surface.header", surface.header),
+                TEXT_CONTRAST,
+            ),
+            (
+                ("text.on_accent", colors.text.on_accent),
+                ("accent", colors.accent),
+                TEXT_CONTRAST,
+            ),
+            (
+                ("accent", colors.accent),
+                ("surface.panel", surface.panel),
+                NON_TEXT_CONTRAST,
+            ),
+            (
+                ("focus_ring", colors.focus_ring),
+                ("surface.panel", surface.panel),
+                NON_TEXT_CONTRAST,
+            ),
+            (
+                ("danger", colors.danger),
+                ("surface.panel", surface.panel),
+                NON_TEXT_CONTRAST,
+            ),
+            (
+                ("warning", colors.warning),
+                ("surface.panel", surface.panel),
+                NON_TEXT_CONTRAST,
+            ),
+            (
+                ("success", colors.success),
+                ("surface.panel", surface.panel),
+                NON_TEXT_CONTRAST,
+            ),
+        ];
+
+        pairs
+            .into_iter()
+            .filter_map(|((foreground, a), (background, b), required)| {
+                let ratio = contrast_ratio(a, b);
+                (ratio < required).then_some(ContrastFailure {
+                    foreground,
+                    background,
+                    ratio,
+                    required,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SetTheme, Themes};
+
+    #[test]
+    fn contrast_ratios() {
+        assert!((contrast_ratio(Color::BLACK, Color::WHITE) - 21.).abs() < 1e-3);
+        assert!((contrast_ratio(Color::WHITE, Color::BLACK) - 21.).abs() < 1e-3);
+        let gray = Color::oklch(0.5, 0., 0.);
+        assert!((contrast_ratio(gray, gray) - 1.).abs() < 1e-6);
+
+        let mut theme = Theme::default();
+        theme.colors.text.primary = theme.colors.surface.panel;
+        let failures = theme.contrast_failures();
+        assert_eq!(failures[0].foreground, "text.primary");
+        assert!(failures
+            .iter()
+            .all(|failure| failure.ratio < failure.required));
+    }
+
+    #[test]
+    fn bundled_themes_have_enough_contrast() {
+        let themes = Themes::default();
+        for name in themes.names() {
+            let failures = themes.get(name).unwrap().contrast_failures();
+            assert!(
+                failures.is_empty(),
+                "{name}:\n{}",
+                failures
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
+    }
+
+    #[test]
+    fn simulate_color_blindness() {
+        let red = Color::oklch(0.63, 0.26, 29.);
+        let green = Color::oklch(0.87, 0.29, 142.);
+        let hue_distance = |a: Color, b: Color| {
+            let (a, b) = (Oklcha::from(a), Oklcha::from(b));
+            let distance = (a.hue - b.hue).rem_euclid(360.);
+            distance.min(360. - distance)
+        };
+
+        // Red and green get close in hue without green or red cones
+        for deficiency in [ColorBlindness::Deuteranopia, ColorBlindness::Protanopia] {
+            let (red, green) = (deficiency.simulate(red), deficiency.simulate(green));
+            assert!(hue_distance(red, green) < 30., "{deficiency:?}");
+        }
+
+        // Grays look the same to everyone
+        let gray = Color::oklch(0.5, 0., 0.);
+        let simulated = Oklcha::from(ColorBlindness::Tritanopia.simulate(gray));
+        assert!((simulated.lightness - 0.5).abs() < 0.01);
+        assert!(simulated.chroma < 0.01);
+
+        let theme = Theme::default().simulate_color_blindness(ColorBlindness::Tritanopia);
+        assert_ne!(theme.colors.accent, Theme::default().colors.accent);
+        assert_eq!(theme.spacing, Theme::default().spacing);
+    }
+
+    #[test]
+    fn simulate_at_runtime() {
+        let mut app = App::new();
+        app.add_plugins(crate::StylesPlugin);
+        app.update();
+
+        app.insert_resource(SimulatedColorBlindness(Some(ColorBlindness::Protanopia)));
+        app.update();
+        let expected = Theme::default().simulate_color_blindness(ColorBlindness::Protanopia);
+        assert_eq!(*app.world().resource::<Theme>(), expected);
+
+        // Switching themes keeps simulating
+        app.world_mut().send_event(SetTheme("Light".to_string()));
+        app.update();
+        let light = app
+            .world()
+            .resource::<Themes>()
+            .get("Light")
+            .unwrap()
+            .clone();
+        let expected = light.simulate_color_blindness(ColorBlindness::Protanopia);
+        assert_eq!(*app.world().resource::<Theme>(), expected);
+
+        app.insert_resource(SimulatedColorBlindness(None));
+        app.update();
+        assert_eq!(*app.world().resource::<Theme>(), light);
+    }
+}
@@ -0,0 +1,548 @@
+//! Named, theme-aware styles applied to UI nodes by their [`StyleClass`], following their
+//! [`InteractionState`].
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{TextRole, Theme};
+
+/// Registers the style classes of the editor's widgets.
+pub(crate) fn register_builtin_classes(app: &mut App) {
+    app.register_style_class("button", |theme| ClassStyle {
+        base: StyleRule {
+            background: Some(theme.colors.surface.raised),
+            border_color: Some(Color::NONE),
+            text_color: Some(theme.colors.text.primary),
+            text_role: Some(TextRole::Label),
+            border_radius: Some(BorderRadius::all(Val::Px(theme.radius.medium))),
+            padding: Some(UiRect::axes(
+                Val::Px(theme.spacing.md),
+                Val::Px(theme.spacing.xs),
+            )),
+            ..default()
+        },
+        hovered: StyleRule {
+            background: Some(theme.colors.surface.raised.lighter(0.05)),
+            ..default()
+        },
+        pressed: StyleRule {
+            background: Some(theme.colors.surface.raised.darker(0.05)),
+            ..default()
+        },
+        focused: StyleRule {
+            border_color: Some(theme.colors.focus_ring),
+            ..default()
+        },
+        disabled: StyleRule {
+            background: Some(theme.colors.surface.panel),
+            text_color: Some(theme.colors.text.disabled),
+            ..default()
+        },
+    })
+    .register_style_class("button.primary", |theme| {
+        accent_button(theme.colors.accent, theme)
+    })
+    .register_style_class("button.danger", |theme| {
+        accent_button(theme.colors.danger, theme)
+    })
+    .register_style_class("pane.header", |theme| ClassStyle {
+        // The title of the pane is a child of its header
+        base: StyleRule {
+            text_color: Some(theme.colors.text.primary),
+            text_role: Some(TextRole::Label),
+            ..pane_bar(
+                BorderRadius::top(Val::Px(theme.radius.medium)),
+                theme.sizes.pane_header,
+                theme,
+            )
+        },
+        ..default()
+    })
+    .register_style_class("pane.footer", |theme| ClassStyle {
+        base: pane_bar(
+            BorderRadius::bottom(Val::Px(theme.radius.medium)),
+            theme.sizes.pane_footer,
+            theme,
+        ),
+        ..default()
+    });
+}
+
+/// A button filled with `color`, layered on the `button` class.
+fn accent_button(color: Color, theme: &Theme) -> ClassStyle {
+    ClassStyle {
+        base: StyleRule {
+            background: Some(color),
+            text_color: Some(theme.colors.text.on_accent),
+            ..default()
+        },
+        hovered: StyleRule {
+            background: Some(color.lighter(0.05)),
+            ..default()
+        },
+        pressed: StyleRule {
+            background: Some(color.darker(0.05)),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+/// The headers and footers of panes.
+fn pane_bar(border_radius: BorderRadius, height: f32, theme: &Theme) -> StyleRule {
+    StyleRule {
+        background: Some(theme.colors.surface.header),
+        border_radius: Some(border_radius),
+        padding: Some(UiRect::axes(
+            Val::Px(theme.spacing.sm),
+            Val::Px(theme.spacing.xs),
+        )),
+        height: Some(Val::Px(height)),
+        ..default()
+    }
+}
+
+/// The properties a style class sets on a node, those left at `None` are left to the node.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StyleRule {
+    /// The [`BackgroundColor`] of the node.
+    pub background: Option<Color>,
+    /// The [`BorderColor`] of the node.
+    pub border_color: Option<Color>,
+    /// The [`TextColor`] of the children of the node, like the label of a button.
+    pub text_color: Option<Color>,
+    /// The [`TextRole`] of the children of the node, setting their [`TextFont`] from the theme.
+    pub text_role: Option<TextRole>,
+    /// The [`BorderRadius`] of the node.
+    pub border_radius: Option<BorderRadius>,
+    /// The border width of the node.
+    pub border: Option<UiRect>,
+    /// The padding of the node.
+    pub padding: Option<UiRect>,
+    /// The height of the node.
+    pub height: Option<Val>,
+}
+
+impl StyleRule {
+    /// Overrides the properties `other` sets.
+    fn merge(&mut self, other: &StyleRule) {
+        let StyleRule {
+            background,
+            border_color,
+            text_color,
+            text_role,
+            border_radius,
+            border,
+            padding,
+            height,
+        } = other.clone();
+        self.background = background.or(self.background);
+        self.border_color = border_color.or(self.border_color);
+        self.text_color = text_color.or(self.text_color);
+        self.text_role = text_role.or(self.text_role);
+        self.border_radius = border_radius.or(self.border_radius);
+        self.border = border.or(self.border);
+        self.padding = padding.or(self.padding);
+        self.height = height.or(self.height);
+    }
+}
+
+/// A style class, with the rules of each [`InteractionState`].
+///
+/// The rules of the states are layered on the base rule, disabled nodes are neither hovered nor pressed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClassStyle {
+    /// The rule of the node at rest.
+    pub base: StyleRule,
+    /// Layered on the base rule when the pointer is over the node.
+    pub hovered: StyleRule,
+    /// Layered on the hovered rule while the node is pressed.
+    pub pressed: StyleRule,
+    /// Layered on the other rules when the node has the keyboard focus.
+    pub focused: StyleRule,
+    /// Layered on the other rules when the node is disabled.
+    pub disabled: StyleRule,
+}
+
+impl ClassStyle {
+    /// The properties of a node in `state`.
+    pub fn resolve(&self, state: InteractionState) -> StyleRule {
+        let mut rule = self.base.clone();
+        if !state.disabled {
+            if state.hovered {
+                rule.merge(&self.hovered);
+            }
+            if state.pressed {
+                rule.merge(&self.pressed);
+            }
+        }
+        if state.focused {
+            rule.merge(&self.focused);
+        }
+        if state.disabled {
+            rule.merge(&self.disabled);
+        }
+        rule
+    }
+
+    /// Layers the rules of `other` on the rules of this class.
+    fn merge(&mut self, other: &ClassStyle) {
+        self.base.merge(&other.base);
+        self.hovered.merge(&other.hovered);
+        self.pressed.merge(&other.pressed);
+        self.focused.merge(&other.focused);
+        self.disabled.merge(&other.disabled);
+    }
+}
+
+/// Creates the style of a class from the current theme.
+type ClassDefinition = Box<dyn Fn(&Theme) -> ClassStyle + Send + Sync>;
+
+/// The style classes, created from the [`Theme`] again whenever it changes.
+///
+/// A dotted class name layers on its parents: `button.primary` starts from the style of `button`.
+#[derive(Resource, Default)]
+pub struct StyleClasses {
+    definitions: HashMap<String, ClassDefinition>,
+    styles: HashMap<String, ClassStyle>,
+    /// Whether classes were added since the styles were created.
+    dirty: bool,
+}
+
+impl StyleClasses {
+    /// Adds the class `name`, replacing the class with the same name.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        definition: impl Fn(&Theme) -> ClassStyle + Send + Sync + 'static,
+    ) {
+        self.definitions.insert(name.into(), Box::new(definition));
+        self.dirty = true;
+    }
+
+    /// The style of the class `name`, layered on its parents.
+    pub fn get(&self, name: &str) -> Option<&ClassStyle> {
+        self.styles.get(name)
+    }
+
+    /// Creates the styles of the classes from `theme`.
+    fn resolve(&mut self, theme: &Theme) {
+        self.styles = self
+            .definitions
+            .keys()
+            .map(|name| {
+                let mut style = ClassStyle::default();
+                let parents = name.match_indices('.').map(|(index, _)| &name[..index]);
+                for class in parents.chain([name.as_str()]) {
+                    if let Some(definition) = self.definitions.get(class) {
+                        style.merge(&definition(theme));
+                    }
+                }
+                (name.clone(), style)
+            })
+            .collect();
+        self.dirty = false;
+    }
+}
+
+/// Adds a style class to the app.
+pub trait StyleClassAppExt {
+    /// Adds the class `name` (like `"button.primary"`), its style is created from the current [`Theme`].
+    /// The [`StylesPlugin`](crate::StylesPlugin) has to be added first.
+    fn register_style_class(
+        &mut self,
+        name: impl Into<String>,
+        definition: impl Fn(&Theme) -> ClassStyle + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl StyleClassAppExt for App {
+    fn register_style_class(
+        &mut self,
+        name: impl Into<String>,
+        definition: impl Fn(&Theme) -> ClassStyle + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.world_mut()
+            .resource_mut::<StyleClasses>()
+            .insert(name, definition);
+        self
+    }
+}
+
+/// Styles a UI node with the class of the given name from the [`StyleClasses`].
+///
+/// The node follows its [`InteractionState`], which is added along with the class.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct StyleClass(pub String);
+
+impl StyleClass {
+    /// The class `name`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
+
+/// The state of a node with a [`StyleClass`].
+///
+/// Hovering and pressing are tracked from the pointer, widgets set whether the node is focused or disabled.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InteractionState {
+    /// The pointer is over the node or its children.
+    pub hovered: bool,
+    /// The node is pressed with the primary button.
+    pub pressed: bool,
+    /// The node has the keyboard focus.
+    pub focused: bool,
+    /// The node can't be interacted with.
+    pub disabled: bool,
+}
+
+/// Creates the styles of the classes when the theme changes or classes are added.
+pub(crate) fn resolve_style_classes(theme: Res<Theme>, mut classes: ResMut<StyleClasses>) {
+    if theme.is_changed() || classes.dirty {
+        classes.resolve(&theme);
+    }
+}
+
+/// Adds the [`InteractionState`] of new styled nodes.
+pub(crate) fn add_interaction_states(
+    mut commands: Commands,
+    nodes: Query<Entity, (Added<StyleClass>, Without<InteractionState>)>,
+) {
+    for entity in &nodes {
+        commands.entity(entity).insert(InteractionState::default());
+    }
+}
+
+pub(crate) fn on_class_over(
+    trigger: Trigger<Pointer<Over>>,
+    mut states: Query<&mut InteractionState>,
+) {
+    if let Ok(mut state) = states.get_mut(trigger.entity()) {
+        state.hovered = true;
+    }
+}
+
+pub(crate) fn on_class_out(
+    trigger: Trigger<Pointer<Out>>,
+    mut states: Query<&mut InteractionState>,
+) {
+    if let Ok(mut state) = states.get_mut(trigger.entity()) {
+        state.hovered = false;
+        state.pressed = false;
+    }
+}
+
+pub(crate) fn on_class_down(
+    trigger: Trigger<Pointer<Down>>,
+    mut states: Query<&mut InteractionState>,
+) {
+    if trigger.event().button != PointerButton::Primary {
+        return;
+    }
+    if let Ok(mut state) = states.get_mut(trigger.entity()) {
+        state.pressed = true;
+    }
+}
+
+pub(crate) fn on_class_up(trigger: Trigger<Pointer<Up>>, mut states: Query<&mut InteractionState>) {
+    if trigger.event().button != PointerButton::Primary {
+        return;
+    }
+    if let Ok(mut state) = states.get_mut(trigger.entity()) {
+        state.pressed = false;
+    }
+}
+
+/// Applies the style classes to the nodes whose class or state changed, or to all of them when the
+/// classes change.
+#[expect(clippy::type_complexity)]
+pub(crate) fn apply_style_classes(
+    theme: Res<Theme>,
+    classes: Res<StyleClasses>,
+    mut nodes: Query<(
+        Ref<StyleClass>,
+        Option<Ref<InteractionState>>,
+        &mut Style,
+        Option<&mut BackgroundColor>,
+        Option<&mut BorderColor>,
+        Option<&mut BorderRadius>,
+        Option<&Children>,
+    )>,
+    mut text_colors: Query<&mut TextColor>,
+    mut text_fonts: Query<&mut TextFont>,
+) {
+    for (class, state, mut style, background, border_color, border_radius, children) in &mut nodes {
+        let state_changed = state.as_ref().is_some_and(DetectChanges::is_changed);
+        if !classes.is_changed() && !class.is_changed() && !state_changed {
+            continue;
+        }
+        let Some(class_style) = classes.get(&class.0) else {
+            warn!("Unknown style class '{}'", class.0);
+            continue;
+        };
+
+        let rule = class_style.resolve(state.as_deref().copied().unwrap_or_default());
+        if let (Some(color), Some(mut background)) = (rule.background, background) {
+            background.set_if_neq(BackgroundColor(color));
+        }
+        if let (Some(color), Some(mut border_color)) = (rule.border_color, border_color) {
+            border_color.set_if_neq(BorderColor(color));
+        }
+        if let (Some(radius), Some(mut border_radius)) = (rule.border_radius, border_radius) {
+            border_radius.set_if_neq(radius);
+        }
+        if let Some(border) = rule.border {
+            if style.border != border {
+                style.border = border;
+            }
+        }
+        if let Some(padding) = rule.padding {
+            if style.padding != padding {
+                style.padding = padding;
+            }
+        }
+        if let Some(height) = rule.height {
+            if style.height != height {
+                style.height = height;
+            }
+        }
+        if let Some(color) = rule.text_color {
+            // The label of a widget is usually a child of its node
+            let entities = children.into_iter().flatten().copied();
+            let mut texts = text_colors.iter_many_mut(entities);
+            while let Some(mut text_color) = texts.fetch_next() {
+                if text_color.0 != color {
+                    text_color.0 = color;
+                }
+            }
+        }
+        if let Some(role) = rule.text_role {
+            let font = theme.typography.font(role);
+            let entities = children.into_iter().flatten().copied();
+            let mut texts = text_fonts.iter_many_mut(entities);
+            while let Some(mut text_font) = texts.fetch_next() {
+                if text_font.font != font.font || text_font.font_size != font.font_size {
+                    *text_font = font.clone();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(crate::StylesPlugin);
+        app
+    }
+
+    #[test]
+    fn layered_classes() {
+        let mut app = app();
+        app.register_style_class("button.primary.large", |theme| ClassStyle {
+            base: StyleRule {
+                padding: Some(UiRect::all(Val::Px(theme.spacing.lg))),
+                ..default()
+            },
+            ..default()
+        });
+        app.update();
+
+        let theme = Theme::default();
+        let classes = app.world().resource::<StyleClasses>();
+        let large = classes.get("button.primary.large").unwrap();
+        assert_eq!(large.base.background, Some(theme.colors.accent));
+        assert_eq!(
+            large.base.border_radius,
+            Some(BorderRadius::all(Val::Px(theme.radius.medium)))
+        );
+        assert_eq!(
+            large.base.padding,
+            Some(UiRect::all(Val::Px(theme.spacing.lg)))
+        );
+        assert_eq!(large.focused.border_color, Some(theme.colors.focus_ring));
+    }
+
+    #[test]
+    fn resolve_states() {
+        let classes = {
+            let mut app = app();
+            app.update();
+            app.world_mut().remove_resource::<StyleClasses>().unwrap()
+        };
+        let button = classes.get("button").unwrap();
+        let theme = Theme::default();
+
+        let hovered = InteractionState {
+            hovered: true,
+            ..default()
+        };
+        assert_ne!(
+            button.resolve(hovered).background,
+            button.resolve(default()).background
+        );
+
+        // Disabled nodes ignore the pointer
+        let disabled = InteractionState {
+            hovered: true,
+            pressed: true,
+            disabled: true,
+            ..default()
+        };
+        let rule = button.resolve(disabled);
+        assert_eq!(rule.background, Some(theme.colors.surface.panel));
+        assert_eq!(rule.text_color, Some(theme.colors.text.disabled));
+    }
+
+    #[test]
+    fn apply_to_nodes() {
+        let mut app = app();
+        let button = app
+            .world_mut()
+            .spawn((NodeBundle::default(), StyleClass::new("button.primary")))
+            .with_child(Text::new("Save"))
+            .id();
+        app.update();
+
+        let theme = Theme::default();
+        let label = app.world().get::<Children>(button).unwrap()[0];
+        assert_eq!(
+            app.world().get::<BackgroundColor>(button).unwrap().0,
+            theme.colors.accent
+        );
+        assert_eq!(
+            app.world().get::<TextColor>(label).unwrap().0,
+            theme.colors.text.on_accent
+        );
+
+        app.world_mut()
+            .get_mut::<InteractionState>(button)
+            .unwrap()
+            .focused = true;
+        app.update();
+        assert_eq!(
+            app.world().get::<BorderColor>(button).unwrap().0,
+            theme.colors.focus_ring
+        );
+
+        // Styled nodes follow the theme
+        app.world_mut().resource_mut::<Theme>().colors.accent = Color::WHITE;
+        app.update();
+        assert_eq!(
+            app.world().get::<BackgroundColor>(button).unwrap().0,
+            Color::WHITE
+        );
+
+        // Labels follow the typography of the theme
+        app.world_mut()
+            .resource_mut::<Theme>()
+            .typography
+            .label
+            .size = 20.;
+        app.update();
+        assert_eq!(app.world().get::<TextFont>(label).unwrap().font_size, 20.);
+    }
+}
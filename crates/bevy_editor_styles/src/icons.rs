@@ -0,0 +1,587 @@
+//! Icons addressed by name, packed into a texture atlas or drawn from an icon font.
+//!
+//! Icon images are white on a transparent background, so they can be tinted with the colors of
+//! the [`Theme`].
+//!
+//! Only the image formats Bevy loads, like PNG, are supported. Bevy can't read SVG files and
+//! rasterizing them would need a new dependency, so icons drawn as SVGs are exported to PNG first.
+
+use std::path::Path;
+
+use bevy::{
+    asset::{LoadState, LoadedFolder, UntypedAssetId},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    ui::UiSystem,
+    utils::HashMap,
+};
+
+use crate::{ColorTokens, IconSizes, Theme};
+
+/// Adds the [`IconRegistry`] and shows the [`Icon`]s.
+///
+/// Needs the asset plugins, which are part of the `DefaultPlugins`.
+pub struct IconPlugin;
+
+impl Plugin for IconPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IconRegistry>()
+            .add_systems(PreUpdate, build_icon_atlas)
+            .add_systems(PostUpdate, update_icons.before(UiSystem::Layout));
+    }
+}
+
+/// Where an icon is drawn from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IconSource {
+    /// A section of the icon atlas.
+    Atlas {
+        /// The atlas image.
+        image: Handle<Image>,
+        /// The layout of the atlas.
+        layout: Handle<TextureAtlasLayout>,
+        /// The index of the icon in the layout.
+        index: usize,
+    },
+    /// A glyph of an icon font.
+    Glyph {
+        /// The icon font.
+        font: Handle<Font>,
+        /// The character of the icon.
+        glyph: char,
+    },
+}
+
+/// The icons of the editor, by name.
+///
+/// Images are packed into a single atlas once they are all loaded, the icons of a folder are named
+/// after their path in the folder without the extension, like `pane/close` for `icons/pane/close.png`.
+#[derive(Resource, Debug, Default)]
+pub struct IconRegistry {
+    /// The images that make up the atlas, by name.
+    images: Vec<(String, Handle<Image>)>,
+    /// Folders that are still loading.
+    folders: Vec<Handle<LoadedFolder>>,
+    /// Whether images were added since the atlas was built.
+    dirty: bool,
+    atlas: Option<IconAtlas>,
+    glyphs: HashMap<String, (Handle<Font>, char)>,
+}
+
+/// The atlas the icon images are packed into.
+#[derive(Debug)]
+struct IconAtlas {
+    image: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+    indices: HashMap<String, usize>,
+}
+
+impl IconRegistry {
+    /// Adds an icon image, replacing the icon with the same name.
+    pub fn add_image(&mut self, name: impl Into<String>, image: Handle<Image>) {
+        let name = name.into();
+        self.images.retain(|(old, _)| *old != name);
+        self.images.push((name, image));
+        self.dirty = true;
+    }
+
+    /// Loads the icon images in the asset folder `path` and its subfolders.
+    ///
+    /// Files that aren't images, like SVGs and credits, are skipped.
+    pub fn load_folder(&mut self, asset_server: &AssetServer, path: impl Into<String>) {
+        self.folders.push(asset_server.load_folder(path.into()));
+    }
+
+    /// Adds the icons of an icon font, as pairs of names and characters.
+    pub fn add_glyphs<N: Into<String>>(
+        &mut self,
+        font: Handle<Font>,
+        glyphs: impl IntoIterator<Item = (N, char)>,
+    ) {
+        for (name, glyph) in glyphs {
+            self.glyphs.insert(name.into(), (font.clone(), glyph));
+        }
+    }
+
+    /// Whether icons are still loading, in which case they may be missing.
+    pub fn is_loading(&self) -> bool {
+        self.dirty || !self.folders.is_empty()
+    }
+
+    /// The icon named `name`, images win over glyphs with the same name.
+    pub fn get(&self, name: &str) -> Option<IconSource> {
+        let image = self.atlas.as_ref().and_then(|atlas| {
+            atlas.indices.get(name).map(|&index| IconSource::Atlas {
+                image: atlas.image.clone(),
+                layout: atlas.layout.clone(),
+                index,
+            })
+        });
+        image.or_else(|| {
+            self.glyphs
+                .get(name)
+                .map(|(font, glyph)| IconSource::Glyph {
+                    font: font.clone(),
+                    glyph: *glyph,
+                })
+        })
+    }
+
+    /// The names of the icons, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .atlas
+            .iter()
+            .flat_map(|atlas| atlas.indices.keys())
+            .chain(self.glyphs.keys())
+            .map(String::as_str)
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+}
+
+/// Packs the icon images into a grid, each icon centered in a cell of the size of the largest icon.
+///
+/// Returns the atlas image, its layout and the index of each icon.
+fn pack_icons(icons: &[(String, Image)]) -> (Image, TextureAtlasLayout, HashMap<String, usize>) {
+    let cell = icons.iter().fold(UVec2::ONE, |cell, (_, image)| {
+        cell.max(UVec2::new(image.width(), image.height()))
+    });
+    let columns = (icons.len() as f32).sqrt().ceil().max(1.) as u32;
+    let rows = (icons.len() as u32).div_ceil(columns).max(1);
+    let size = cell * UVec2::new(columns, rows);
+
+    let mut data = vec![0; (size.x * size.y * 4) as usize];
+    let mut indices = HashMap::new();
+    for (index, (name, image)) in icons.iter().enumerate() {
+        let column = index as u32 % columns;
+        let row = index as u32 / columns;
+        let offset = UVec2::new(column, row) * cell + (cell - image.size()) / 2;
+        let row_bytes = (image.width() * 4) as usize;
+        for (y, source) in image.data.chunks_exact(row_bytes).enumerate() {
+            let start = (((offset.y + y as u32) * size.x + offset.x) * 4) as usize;
+            data[start..start + row_bytes].copy_from_slice(source);
+        }
+        indices.insert(name.clone(), index);
+    }
+
+    let image = Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    let layout = TextureAtlasLayout::from_grid(cell, columns, rows, None, None);
+    (image, layout, indices)
+}
+
+/// The name of an icon of the folder at `folder`, its path in the folder without the extension.
+fn icon_name(folder: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(folder).ok()?.with_extension("");
+    let parts: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Adds the images of loaded folders and packs the atlas once all the images are loaded.
+///
+/// Folders and images that failed to load are left out.
+fn build_icon_atlas(
+    mut registry: ResMut<IconRegistry>,
+    asset_server: Res<AssetServer>,
+    folders: Res<Assets<LoadedFolder>>,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    if !registry.is_loading() {
+        return;
+    }
+
+    // Otherwise the atlas would wait for them forever
+    let failed = |id: UntypedAssetId| matches!(asset_server.load_state(id), LoadState::Failed(_));
+    registry.folders.retain(|handle| {
+        let failed = failed(handle.id().untyped());
+        if failed {
+            warn!("Failed to load the icon folder {:?}", handle.path());
+        }
+        !failed
+    });
+    registry.images.retain(|(name, handle)| {
+        let failed = failed(handle.id().untyped());
+        if failed {
+            warn!("Failed to load the icon '{name}'");
+        }
+        !failed
+    });
+
+    let loaded: Vec<_> = registry
+        .folders
+        .iter()
+        .filter(|handle| folders.contains(*handle))
+        .cloned()
+        .collect();
+    if !loaded.is_empty() {
+        registry.folders.retain(|handle| !folders.contains(handle));
+    }
+    for handle in loaded {
+        let folder = folders.get(&handle).expect("the folder is loaded");
+        let folder_path = handle.path().map(|path| path.path().to_path_buf());
+        for file in &folder.handles {
+            let Ok(image) = file.clone().try_typed::<Image>() else {
+                continue;
+            };
+            let name = file
+                .path()
+                .zip(folder_path.as_deref())
+                .and_then(|(path, folder)| icon_name(folder, path.path()));
+            if let Some(name) = name {
+                registry.add_image(name, image);
+            }
+        }
+    }
+
+    if !registry.dirty
+        || !registry
+            .images
+            .iter()
+            .all(|(_, image)| images.contains(image))
+    {
+        return;
+    }
+
+    let icons: Vec<(String, Image)> = registry
+        .images
+        .iter()
+        .filter_map(|(name, handle)| {
+            let image = images.get(handle)?;
+            let image = if image.texture_descriptor.format == TextureFormat::Rgba8UnormSrgb {
+                Some(image.clone())
+            } else {
+                image.convert(TextureFormat::Rgba8UnormSrgb)
+            };
+            if image.is_none() {
+                warn!("Unsupported format of the icon '{name}'");
+            }
+            image.map(|image| (name.clone(), image))
+        })
+        .collect();
+
+    let (image, layout, indices) = pack_icons(&icons);
+    registry.atlas = Some(IconAtlas {
+        image: images.add(image),
+        layout: layouts.add(layout),
+        indices,
+    });
+    registry.dirty = false;
+}
+
+/// The size of an [`Icon`], one of the [`IconSizes`] of the theme.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum IconSize {
+    /// See [`IconSizes::small`].
+    Small,
+    /// See [`IconSizes::medium`].
+    #[default]
+    Medium,
+    /// See [`IconSizes::large`].
+    Large,
+}
+
+impl IconSize {
+    /// The size in logical pixels.
+    pub fn pixels(self, sizes: &IconSizes) -> f32 {
+        match self {
+            Self::Small => sizes.small,
+            Self::Medium => sizes.medium,
+            Self::Large => sizes.large,
+        }
+    }
+}
+
+/// The color an [`Icon`] is tinted with, one of the [`ColorTokens`] of the theme.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum IconColor {
+    /// The color of regular text.
+    #[default]
+    Primary,
+    /// The color of less important text.
+    Secondary,
+    /// The color of disabled elements.
+    Disabled,
+    /// The color of text on top of the accent color.
+    OnAccent,
+    /// The accent color.
+    Accent,
+    /// The color of errors.
+    Danger,
+    /// The color of warnings.
+    Warning,
+    /// The color of successful operations.
+    Success,
+}
+
+impl IconColor {
+    /// The color of the token.
+    pub fn color(self, colors: &ColorTokens) -> Color {
+        match self {
+            Self::Primary => colors.text.primary,
+            Self::Secondary => colors.text.secondary,
+            Self::Disabled => colors.text.disabled,
+            Self::OnAccent => colors.text.on_accent,
+            Self::Accent => colors.accent,
+            Self::Danger => colors.danger,
+            Self::Warning => colors.warning,
+            Self::Success => colors.success,
+        }
+    }
+}
+
+/// Shows the icon of the [`IconRegistry`] with the given name, added to a UI node.
+///
+/// ```ignore
+/// commands.spawn((NodeBundle::default(), Icon::new("pane/close").with_color(IconColor::Secondary)));
+/// ```
+///
+/// The node is sized to the icon and follows the [`Theme`] when it changes.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct Icon {
+    /// The name of the icon.
+    pub name: String,
+    /// The size of the icon.
+    pub size: IconSize,
+    /// The color the icon is tinted with.
+    pub color: IconColor,
+}
+
+impl Icon {
+    /// A medium icon in the color of regular text.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            size: default(),
+            color: default(),
+        }
+    }
+
+    /// Sets the size of the icon.
+    pub fn with_size(mut self, size: IconSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the color of the icon.
+    pub fn with_color(mut self, color: IconColor) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// Draws the [`Icon`]s as atlas images or glyphs, when they, the icons or the theme change.
+fn update_icons(
+    mut commands: Commands,
+    registry: Res<IconRegistry>,
+    theme: Res<Theme>,
+    mut icons: Query<(Entity, Ref<Icon>, &mut Style)>,
+) {
+    let restyle_all = registry.is_changed() || theme.is_changed();
+    for (entity, icon, mut style) in &mut icons {
+        if !restyle_all && !icon.is_changed() {
+            continue;
+        }
+
+        let size = icon.size.pixels(&theme.icon_sizes);
+        let color = icon.color.color(&theme.colors);
+        style.width = Val::Px(size);
+        style.height = Val::Px(size);
+
+        let mut entity = commands.entity(entity);
+        match registry.get(&icon.name) {
+            Some(IconSource::Atlas {
+                image,
+                layout,
+                index,
+            }) => {
+                entity.remove::<(Text, TextFont, TextColor)>().insert((
+                    UiImage::new(image).with_color(color),
+                    TextureAtlas { layout, index },
+                ));
+            }
+            Some(IconSource::Glyph { font, glyph }) => {
+                entity.remove::<(UiImage, TextureAtlas)>().insert((
+                    Text::new(glyph.to_string()),
+                    TextFont {
+                        font,
+                        font_size: size,
+                        ..default()
+                    },
+                    TextColor(color),
+                ));
+            }
+            None => {
+                if !registry.is_loading() {
+                    warn!("Unknown icon '{}'", icon.name);
+                }
+                entity.remove::<(UiImage, TextureAtlas, Text, TextFont, TextColor)>();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32, pixel: [u8; 4]) -> Image {
+        Image::new(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            pixel.repeat((width * height) as usize),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        )
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<TextureAtlasLayout>()
+            .add_plugins((crate::StylesPlugin, IconPlugin));
+        app
+    }
+
+    #[test]
+    fn pack_atlas() {
+        let icons = [
+            ("close".to_string(), image(4, 4, [255; 4])),
+            ("open".to_string(), image(2, 2, [255, 0, 0, 255])),
+            ("play".to_string(), image(4, 4, [255; 4])),
+        ];
+        let (atlas, layout, indices) = pack_icons(&icons);
+        assert_eq!(atlas.size(), UVec2::new(8, 8));
+        assert_eq!(layout.textures.len(), 4);
+        assert_eq!(indices["play"], 2);
+
+        // Smaller icons are centered in their cell
+        let pixel = |x: u32, y: u32| {
+            let start = ((y * 8 + x) * 4) as usize;
+            &atlas.data[start..start + 4]
+        };
+        assert_eq!(pixel(4, 0), [0; 4]);
+        assert_eq!(pixel(5, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(0, 7), [255; 4]);
+    }
+
+    #[test]
+    fn icon_names() {
+        assert_eq!(
+            icon_name(Path::new("icons"), Path::new("icons/pane/close.png")).as_deref(),
+            Some("pane/close")
+        );
+        assert_eq!(
+            icon_name(Path::new("icons"), Path::new("logo/bevy.png")),
+            None
+        );
+    }
+
+    #[test]
+    fn show_icons() {
+        let mut app = app();
+        let close = app
+            .world_mut()
+            .resource_mut::<Assets<Image>>()
+            .add(image(16, 16, [255; 4]));
+        let font = Handle::<Font>::default();
+        let mut registry = app.world_mut().resource_mut::<IconRegistry>();
+        registry.add_image("close", close);
+        registry.add_glyphs(font.clone(), [("play", '\u{e037}')]);
+
+        let close = app
+            .world_mut()
+            .spawn((
+                NodeBundle::default(),
+                Icon::new("close").with_color(IconColor::Danger),
+            ))
+            .id();
+        let play = app
+            .world_mut()
+            .spawn((
+                NodeBundle::default(),
+                Icon::new("play").with_size(IconSize::Large),
+            ))
+            .id();
+        app.update();
+
+        let registry = app.world().resource::<IconRegistry>();
+        assert!(!registry.is_loading());
+        assert_eq!(registry.names(), ["close", "play"]);
+
+        let theme = Theme::default();
+        let world = app.world();
+        assert_eq!(
+            world.get::<UiImage>(close).unwrap().color,
+            theme.colors.danger
+        );
+        assert_eq!(world.get::<TextureAtlas>(close).unwrap().index, 0);
+        assert_eq!(
+            world.get::<Style>(close).unwrap().width,
+            Val::Px(theme.icon_sizes.medium)
+        );
+        assert_eq!(world.get::<Text>(play).unwrap().0, "\u{e037}");
+        assert_eq!(
+            world.get::<TextFont>(play).unwrap().font_size,
+            theme.icon_sizes.large
+        );
+
+        // Icons follow the theme
+        app.world_mut().resource_mut::<Theme>().colors.danger = Color::WHITE;
+        app.update();
+        assert_eq!(
+            app.world().get::<UiImage>(close).unwrap().color,
+            Color::WHITE
+        );
+    }
+
+    #[test]
+    fn skip_failed_loads() {
+        let mut app = app();
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let close = app
+            .world_mut()
+            .resource_mut::<Assets<Image>>()
+            .add(image(16, 16, [255; 4]));
+        let mut registry = app.world_mut().resource_mut::<IconRegistry>();
+        registry.add_image("close", close);
+        registry.add_image("missing", asset_server.load("icons/missing.png"));
+        registry.load_folder(&asset_server, "missing_icons");
+
+        // Loading happens in the background
+        for _ in 0..100 {
+            app.update();
+            if !app.world().resource::<IconRegistry>().is_loading() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        let registry = app.world().resource::<IconRegistry>();
+        assert!(!registry.is_loading());
+        assert_eq!(registry.names(), ["close"]);
+    }
+}
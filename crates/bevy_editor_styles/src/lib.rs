@@ -1,46 +1,116 @@
 //! Palette plugin for the Bevy Editor. This plugin provides a color palette for the editor's UI.
-use bevy::prelude::*;
+use bevy::{prelude::*, ui::UiSystem};
+use serde::{Deserialize, Serialize};
+
+mod accessibility;
+mod classes;
+mod icons;
+mod themes;
+mod tokens;
+mod ui_settings;
+
+pub use accessibility::{
+    contrast_ratio, ColorBlindness, ContrastFailure, SimulatedColorBlindness, NON_TEXT_CONTRAST,
+    TEXT_CONTRAST,
+};
+pub use classes::{
+    ClassStyle, InteractionState, StyleClass, StyleClassAppExt, StyleClasses, StyleRule,
+};
+pub use icons::{Icon, IconColor, IconPlugin, IconRegistry, IconSize, IconSource};
+pub use themes::{OklchColor, SetTheme, ThemeError, Themes};
+pub use tokens::{
+    ColorTokens, ControlColors, FontFamily, FontWeight, IconSizes, RadiusScale, SizeTokens,
+    SpacingScale, SurfaceColors, TextColors, TextRole, TextToken, Typography,
+};
+pub use ui_settings::{Density, UiSettings, MAX_UI_SCALE, MIN_UI_SCALE};
 
 /// The Pallet Plugin.
 pub struct StylesPlugin;
 
 impl Plugin for StylesPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Theme>();
+        app.init_resource::<Theme>()
+            .init_resource::<Themes>()
+            .init_resource::<SimulatedColorBlindness>()
+            .init_resource::<UiSettings>()
+            .init_resource::<UiScale>()
+            .register_type::<UiSettings>()
+            .add_event::<SetTheme>()
+            .init_resource::<StyleClasses>()
+            .observe(classes::on_class_over)
+            .observe(classes::on_class_out)
+            .observe(classes::on_class_down)
+            .observe(classes::on_class_up)
+            .add_systems(
+                PreUpdate,
+                (
+                    themes::switch_theme,
+                    ui_settings::apply_ui_scale.run_if(resource_changed::<UiSettings>),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    classes::resolve_style_classes,
+                    classes::add_interaction_states,
+                    classes::apply_style_classes,
+                )
+                    .chain()
+                    .before(UiSystem::Layout),
+            );
+        classes::register_builtin_classes(app);
     }
 }
 
 /// The core resource for the editor's color palette. This resource is used to store the current theme of the editor.
 /// All colors in the editor should be derived from this resource.
 /// All colors should use OKLCH color space, use <https://oklch.com/> to get colors. This ensures that the colors are perceptually uniform and work well for accessibility such as color blind adjustments.
-#[derive(Resource)]
+///
+/// The theme is made of semantic tokens: widgets pick colors, spacings and font sizes by their role
+/// instead of hard-coding them.
+///
+/// The theme is replaced when switching to another of the [`Themes`] with [`SetTheme`],
+/// UI showing it should be restyled when it changes rather than only reading it at spawn time.
+/// Themes are read from files with [`Theme::load`].
+///
+/// [`Theme::contrast_failures`] checks that text stays readable on the theme, and
+/// [`SimulatedColorBlindness`] previews it as seen with a color vision deficiency.
+/// Its spacings and sizes follow the [`Density`] of the [`UiSettings`].
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Theme {
-    /// The background color of the editor.
-    pub background_color: BackgroundColor,
-    /// The background color of the panes in the editor.
-    pub pane_header_background_color: BackgroundColor,
-    /// The background color of the content area of panes.
-    pub pane_area_background_color: BackgroundColor,
-    /// The text color of the editor.
-    pub text_color: Color,
-    /// The color of the menu bar.
-    pub menu_bar_color: BackgroundColor,
-    /// The Common Border Radius for the Editor.
-    pub border_radius: BorderRadius,
-    /// Pane header Border Radius
-    pub pane_header_border_radius: BorderRadius,
+    /// The name the theme is selected by.
+    pub name: String,
+    /// The colors, by role.
+    #[serde(default)]
+    pub colors: ColorTokens,
+    /// The spacing between and inside elements.
+    #[serde(default)]
+    pub spacing: SpacingScale,
+    /// The corner radii.
+    #[serde(default)]
+    pub radius: RadiusScale,
+    /// The sizes of the recurring parts of the editor.
+    #[serde(default)]
+    pub sizes: SizeTokens,
+    /// The sizes of icons.
+    #[serde(default)]
+    pub icon_sizes: IconSizes,
+    /// The font sizes and weights.
+    #[serde(default)]
+    pub typography: Typography,
 }
 
 impl Default for Theme {
     fn default() -> Self {
         Self {
-            background_color: BackgroundColor(Color::oklch(0.27, 0.0, 0.0)),
-            pane_header_background_color: BackgroundColor(Color::oklch(0.215, 0.0, 0.0)),
-            pane_area_background_color: BackgroundColor(Color::oklch(0.23, 0.0, 0.0)),
-            text_color: Color::oklch(0.9219, 0.0, 0.0),
-            menu_bar_color: BackgroundColor(Color::oklch(0.209, 0.0, 0.0)),
-            border_radius: BorderRadius::all(Val::Px(6.)),
-            pane_header_border_radius: BorderRadius::top(Val::Px(6.)),
+            name: "Dark".to_string(),
+            colors: default(),
+            spacing: default(),
+            radius: default(),
+            sizes: default(),
+            icon_sizes: default(),
+            typography: default(),
         }
     }
 }
@@ -0,0 +1,376 @@
+//! Themes defined in TOML or RON files, and switching between them at runtime.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{SimulatedColorBlindness, Theme, UiSettings};
+
+/// The themes shipped with the editor, the first one is the default.
+const BUNDLED_THEMES: [&str; 3] = [
+    include_str!("../themes/dark.toml"),
+    include_str!("../themes/light.toml"),
+    include_str!("../themes/high_contrast.toml"),
+];
+
+/// Errors that can occur when loading a theme file.
+#[derive(Debug, thiserror::Error)]
+pub enum ThemeError {
+    /// The theme file could not be read.
+    #[error("Could not read {}: {source}", path.display())]
+    Io {
+        /// The path of the theme file.
+        path: PathBuf,
+        /// The underlying error.
+        source: std::io::Error,
+    },
+    /// The TOML theme file is invalid.
+    #[error("Invalid theme {}: {source}", path.display())]
+    Toml {
+        /// The path of the theme file.
+        path: PathBuf,
+        /// The underlying error.
+        source: toml::de::Error,
+    },
+    /// The RON theme file is invalid.
+    #[error("Invalid theme {}: {source}", path.display())]
+    Ron {
+        /// The path of the theme file.
+        path: PathBuf,
+        /// The underlying error.
+        source: ron::error::SpannedError,
+    },
+    /// The file is neither a `.toml` nor a `.ron` file.
+    #[error("Unsupported theme file {}, expected a .toml or .ron file", .0.display())]
+    UnsupportedFormat(PathBuf),
+}
+
+/// A color in the OKLCH color space, written as `[lightness, chroma, hue]` or
+/// `[lightness, chroma, hue, alpha]` in theme files.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<f32>", into = "Vec<f32>")]
+pub struct OklchColor {
+    /// The perceived lightness, from 0 to 1.
+    pub lightness: f32,
+    /// The chroma, from 0 (gray) to about 0.37.
+    pub chroma: f32,
+    /// The hue angle in degrees.
+    pub hue: f32,
+    /// The opacity, from 0 to 1.
+    pub alpha: f32,
+}
+
+impl TryFrom<Vec<f32>> for OklchColor {
+    type Error = String;
+
+    fn try_from(values: Vec<f32>) -> Result<Self, Self::Error> {
+        match values[..] {
+            [lightness, chroma, hue] => Ok(Self {
+                lightness,
+                chroma,
+                hue,
+                alpha: 1.,
+            }),
+            [lightness, chroma, hue, alpha] => Ok(Self {
+                lightness,
+                chroma,
+                hue,
+                alpha,
+            }),
+            _ => Err(format!(
+                "expected [lightness, chroma, hue] with an optional alpha, found {} values",
+                values.len()
+            )),
+        }
+    }
+}
+
+impl From<OklchColor> for Vec<f32> {
+    fn from(color: OklchColor) -> Self {
+        if color.alpha == 1. {
+            vec![color.lightness, color.chroma, color.hue]
+        } else {
+            vec![color.lightness, color.chroma, color.hue, color.alpha]
+        }
+    }
+}
+
+impl From<OklchColor> for Color {
+    fn from(color: OklchColor) -> Self {
+        Color::oklcha(color.lightness, color.chroma, color.hue, color.alpha)
+    }
+}
+
+impl Theme {
+    /// Reads a theme from a `.toml` or `.ron` file, the tokens that are left out keep their
+    /// default value.
+    ///
+    /// ```toml
+    /// name = "Dark"
+    ///
+    /// [colors]
+    /// accent = [0.65, 0.13, 250.0]
+    ///
+    /// [colors.surface]
+    /// background = [0.27, 0.0, 0.0]
+    /// # ...
+    ///
+    /// [typography]
+    /// body = { size = 14.0, weight = "regular" }
+    /// ```
+    pub fn load(path: &Path) -> Result<Self, ThemeError> {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        if !matches!(extension, Some("toml" | "ron")) {
+            return Err(ThemeError::UnsupportedFormat(path.to_path_buf()));
+        }
+
+        let contents = std::fs::read_to_string(path).map_err(|source| ThemeError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        if extension == Some("toml") {
+            toml::from_str(&contents).map_err(|source| ThemeError::Toml {
+                path: path.to_path_buf(),
+                source,
+            })
+        } else {
+            ron::from_str(&contents).map_err(|source| ThemeError::Ron {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+    }
+}
+
+/// (De)serializes colors as [`OklchColor`]s, for `#[serde(with = "oklch")]`.
+pub(crate) mod oklch {
+    use bevy::color::{Color, Oklcha};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::OklchColor;
+
+    pub(crate) fn serialize<S: Serializer>(
+        color: &Color,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let color = Oklcha::from(*color);
+        OklchColor {
+            lightness: color.lightness,
+            chroma: color.chroma,
+            hue: color.hue,
+            alpha: color.alpha,
+        }
+        .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Color, D::Error> {
+        OklchColor::deserialize(deserializer).map(Color::from)
+    }
+}
+
+/// The themes that can be switched to with [`SetTheme`], starting with the bundled
+/// dark, light and high contrast themes.
+#[derive(Resource, Debug)]
+pub struct Themes {
+    themes: Vec<Theme>,
+}
+
+impl Default for Themes {
+    fn default() -> Self {
+        let themes = BUNDLED_THEMES
+            .iter()
+            .map(|theme| toml::from_str(theme).expect("the bundled themes are valid"))
+            .collect();
+        Self { themes }
+    }
+}
+
+impl Themes {
+    /// The theme named `name`.
+    pub fn get(&self, name: &str) -> Option<&Theme> {
+        self.themes.iter().find(|theme| theme.name == name)
+    }
+
+    /// The names of the themes, the bundled ones first.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.themes.iter().map(|theme| theme.name.as_str())
+    }
+
+    /// Adds a theme, replacing the theme with the same name.
+    pub fn insert(&mut self, theme: Theme) {
+        match self.themes.iter_mut().find(|old| old.name == theme.name) {
+            Some(old) => *old = theme,
+            None => self.themes.push(theme),
+        }
+    }
+
+    /// Adds the theme of a `.toml` or `.ron` file.
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<(), ThemeError> {
+        self.insert(Theme::load(path.as_ref())?);
+        Ok(())
+    }
+
+    /// Adds the themes of the `.toml` and `.ron` files in `dir`, returning the files that failed.
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> Vec<ThemeError> {
+        let dir = dir.as_ref();
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(source) => {
+                return vec![ThemeError::Io {
+                    path: dir.to_path_buf(),
+                    source,
+                }]
+            }
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                matches!(
+                    path.extension().and_then(|extension| extension.to_str()),
+                    Some("toml" | "ron")
+                )
+            })
+            .collect();
+        paths.sort();
+        paths
+            .into_iter()
+            .filter_map(|path| self.load_file(path).err())
+            .collect()
+    }
+}
+
+/// Switches the [`Theme`] to the theme of [`Themes`] with the given name.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct SetTheme(pub String);
+
+/// Applies the themes of [`SetTheme`] events, along with the [`UiSettings`] and the
+/// [`SimulatedColorBlindness`] when they change.
+pub(crate) fn switch_theme(
+    mut events: EventReader<SetTheme>,
+    themes: Res<Themes>,
+    ui_settings: Res<UiSettings>,
+    simulation: Res<SimulatedColorBlindness>,
+    mut theme: ResMut<Theme>,
+) {
+    let mut switch_to =
+        (needs_restyle(&ui_settings) || needs_restyle(&simulation)).then(|| theme.name.clone());
+    for SetTheme(name) in events.read() {
+        if themes.get(name).is_some() {
+            switch_to = Some(name.clone());
+        } else {
+            warn!("Unknown theme '{name}'");
+        }
+    }
+
+    let Some(new_theme) = switch_to.and_then(|name| themes.get(&name)) else {
+        return;
+    };
+    let fonts = std::mem::take(&mut theme.typography.fonts);
+    *theme = new_theme.with_density(ui_settings.density);
+    if let Some(deficiency) = simulation.0 {
+        *theme = theme.simulate_color_blindness(deficiency);
+    }
+    theme.typography.fonts = fonts;
+}
+
+/// Whether `setting` changed the look of the theme.
+///
+/// Settings added with their default value keep the theme, which may have been inserted by the app.
+fn needs_restyle<T: Resource + Default + PartialEq>(setting: &Res<T>) -> bool {
+    setting.is_changed() && !(setting.is_added() && **setting == T::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SpacingScale, TextRole};
+
+    #[test]
+    fn bundled_themes() {
+        let themes = Themes::default();
+        assert_eq!(
+            themes.names().collect::<Vec<_>>(),
+            ["Dark", "Light", "High Contrast"]
+        );
+
+        // The dark theme is the default theme
+        assert_eq!(themes.get("Dark"), Some(&Theme::default()));
+    }
+
+    #[test]
+    fn partial_theme() {
+        let theme: Theme = toml::from_str(
+            "name = \"Warm\"\n[colors]\naccent = [0.7, 0.15, 50.0, 0.5]\n[typography]\nbody = { size = 15.0, weight = \"medium\" }",
+        )
+        .unwrap();
+        assert_eq!(theme.colors.accent, Color::oklcha(0.7, 0.15, 50., 0.5));
+        assert_eq!(theme.typography.font(TextRole::Body).font_size, 15.);
+        assert_eq!(theme.spacing, SpacingScale::default());
+
+        // Unknown tokens are rejected
+        assert!(
+            toml::from_str::<Theme>("name = \"Typo\"\n[colors]\naccnet = [0.7, 0.15, 50.0]")
+                .is_err()
+        );
+
+        // Themes need a name
+        assert!(toml::from_str::<Theme>("[colors]\naccent = [0.7, 0.15, 50.0]").is_err());
+    }
+
+    #[test]
+    fn load_theme_files() {
+        let dir =
+            std::env::temp_dir().join(format!("bevy_editor_styles_themes_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut solarized = Themes::default().get("Dark").unwrap().clone();
+        solarized.name = "Solarized".to_string();
+        solarized.colors.surface.background = Color::oklcha(0.27, 0.05, 220., 0.9);
+        std::fs::write(
+            dir.join("solarized.ron"),
+            ron::to_string(&solarized).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("broken.toml"),
+            "name = \"Broken\"\n[colors]\naccent = [0.7]",
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let mut themes = Themes::default();
+        let errors = themes.load_dir(&dir);
+        assert!(matches!(errors[..], [ThemeError::Toml { .. }]));
+        assert_eq!(themes.get("Solarized"), Some(&solarized));
+        assert!(matches!(
+            themes.load_file(dir.join("notes.txt")),
+            Err(ThemeError::UnsupportedFormat(_))
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn switch_at_runtime() {
+        let mut app = App::new();
+        app.add_plugins(crate::StylesPlugin);
+
+        app.world_mut().send_event(SetTheme("Light".to_string()));
+        app.update();
+        assert_eq!(app.world().resource::<Theme>().name, "Light");
+        assert_eq!(
+            app.world().resource::<Theme>().colors.text.primary,
+            Color::oklch(0.25, 0., 0.)
+        );
+
+        // Unknown themes are ignored
+        app.world_mut().send_event(SetTheme("Missing".to_string()));
+        app.update();
+        assert_eq!(app.world().resource::<Theme>().name, "Light");
+    }
+}
@@ -0,0 +1,428 @@
+//! The semantic design tokens the [`Theme`](crate::Theme) is made of.
+//!
+//! Widgets pick tokens by their role (a surface level, the accent, a font size) rather than
+//! hard-coding values, so themes can restyle the whole editor consistently.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::themes::oklch;
+
+/// The colors of the editor, by role.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorTokens {
+    /// The backgrounds the UI is layered on.
+    pub surface: SurfaceColors,
+    /// The colors of text.
+    pub text: TextColors,
+    /// The colors of draggable and clickable controls.
+    pub control: ControlColors,
+    /// Highlights selected and active elements.
+    #[serde(with = "oklch")]
+    pub accent: Color,
+    /// Errors and destructive actions.
+    #[serde(with = "oklch")]
+    pub danger: Color,
+    /// Warnings.
+    #[serde(with = "oklch")]
+    pub warning: Color,
+    /// Successful operations.
+    #[serde(with = "oklch")]
+    pub success: Color,
+    /// Borders and separators.
+    #[serde(with = "oklch")]
+    pub border: Color,
+    /// The outline of the focused element.
+    #[serde(with = "oklch")]
+    pub focus_ring: Color,
+    /// Dims the UI behind overlays and previews, usually translucent.
+    #[serde(with = "oklch")]
+    pub scrim: Color,
+}
+
+impl Default for ColorTokens {
+    fn default() -> Self {
+        Self {
+            surface: default(),
+            text: default(),
+            control: default(),
+            accent: Color::oklch(0.65, 0.13, 250.0),
+            danger: Color::oklch(0.63, 0.19, 25.0),
+            warning: Color::oklch(0.8, 0.15, 80.0),
+            success: Color::oklch(0.72, 0.16, 150.0),
+            border: Color::oklch(0.33, 0.0, 0.0),
+            focus_ring: Color::oklch(0.65, 0.13, 250.0),
+            scrim: Color::oklcha(0.0, 0.0, 0.0, 0.4),
+        }
+    }
+}
+
+impl ColorTokens {
+    /// All the colors, to transform them at once.
+    pub(crate) fn colors_mut(&mut self) -> [&mut Color; 19] {
+        let Self {
+            surface,
+            text,
+            control,
+            accent,
+            danger,
+            warning,
+            success,
+            border,
+            focus_ring,
+            scrim,
+        } = self;
+        [
+            &mut surface.sunken,
+            &mut surface.background,
+            &mut surface.panel,
+            &mut surface.header,
+            &mut surface.raised,
+            &mut text.primary,
+            &mut text.secondary,
+            &mut text.disabled,
+            &mut text.on_accent,
+            &mut control.track,
+            &mut control.handle,
+            &mut control.grip,
+            accent,
+            danger,
+            warning,
+            success,
+            border,
+            focus_ring,
+            scrim,
+        ]
+    }
+}
+
+/// The surface levels the UI is layered on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SurfaceColors {
+    /// Wells set into the surrounding surface.
+    #[serde(with = "oklch")]
+    pub sunken: Color,
+    /// The background of the editor, between the panes and behind the menu bar.
+    #[serde(with = "oklch")]
+    pub background: Color,
+    /// The content area of panes.
+    #[serde(with = "oklch")]
+    pub panel: Color,
+    /// The headers and footers of panes.
+    #[serde(with = "oklch")]
+    pub header: Color,
+    /// Tooltips, popups and other elements floating above the panes.
+    #[serde(with = "oklch")]
+    pub raised: Color,
+}
+
+impl Default for SurfaceColors {
+    fn default() -> Self {
+        Self {
+            sunken: Color::oklch(0.09, 0.0, 0.0),
+            background: Color::oklch(0.27, 0.0, 0.0),
+            panel: Color::oklch(0.23, 0.0, 0.0),
+            header: Color::oklch(0.215, 0.0, 0.0),
+            raised: Color::oklch(0.32, 0.0, 0.0),
+        }
+    }
+}
+
+/// The colors of text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TextColors {
+    /// Regular text.
+    #[serde(with = "oklch")]
+    pub primary: Color,
+    /// Less important text, like hints and descriptions.
+    #[serde(with = "oklch")]
+    pub secondary: Color,
+    /// The text of disabled elements.
+    #[serde(with = "oklch")]
+    pub disabled: Color,
+    /// Text on top of the accent color.
+    #[serde(with = "oklch")]
+    pub on_accent: Color,
+}
+
+impl Default for TextColors {
+    fn default() -> Self {
+        Self {
+            primary: Color::oklch(0.9219, 0.0, 0.0),
+            secondary: Color::oklch(0.75, 0.0, 0.0),
+            disabled: Color::oklch(0.55, 0.0, 0.0),
+            on_accent: Color::oklch(0.2, 0.0, 0.0),
+        }
+    }
+}
+
+/// The colors of draggable and clickable controls, like scrollbars and resize handles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ControlColors {
+    /// The track a handle moves along.
+    #[serde(with = "oklch")]
+    pub track: Color,
+    /// Handles like scrollbar thumbs, and hovered resize handles.
+    #[serde(with = "oklch")]
+    pub handle: Color,
+    /// Small grips marking where something can be dragged.
+    #[serde(with = "oklch")]
+    pub grip: Color,
+}
+
+impl Default for ControlColors {
+    fn default() -> Self {
+        Self {
+            track: Color::oklch(0.25, 0.0, 0.0),
+            handle: Color::oklch(0.4, 0.0, 0.0),
+            grip: Color::oklch(0.45, 0.0, 0.0),
+        }
+    }
+}
+
+/// The spacing between and inside elements, in logical pixels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpacingScale {
+    /// Gaps between tightly packed elements.
+    pub xs: f32,
+    /// The padding of bars and headers.
+    pub sm: f32,
+    /// The padding of regular content.
+    pub md: f32,
+    /// Gaps between groups of elements.
+    pub lg: f32,
+    /// Gaps between sections.
+    pub xl: f32,
+}
+
+impl Default for SpacingScale {
+    fn default() -> Self {
+        Self {
+            xs: 3.,
+            sm: 5.,
+            md: 8.,
+            lg: 12.,
+            xl: 16.,
+        }
+    }
+}
+
+/// The corner radii, in logical pixels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RadiusScale {
+    /// Small elements, like tags and checkboxes.
+    pub small: f32,
+    /// Panes, buttons and most other boxes.
+    pub medium: f32,
+    /// Large floating elements, like dialogs.
+    pub large: f32,
+}
+
+impl Default for RadiusScale {
+    fn default() -> Self {
+        Self {
+            small: 2.,
+            medium: 6.,
+            large: 10.,
+        }
+    }
+}
+
+/// The sizes of the recurring parts of the editor, in logical pixels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SizeTokens {
+    /// The height of pane headers.
+    pub pane_header: f32,
+    /// The height of pane footers.
+    pub pane_footer: f32,
+    /// The height of the menu bar.
+    pub menu_bar: f32,
+    /// The thickness of scrollbars.
+    pub scrollbar: f32,
+    /// The padding around the area of a pane, half of the gap between panes.
+    pub pane_padding: f32,
+    /// The thickness of the area resize handles can be grabbed by.
+    pub resize_handle: f32,
+    /// The length of the grips in the center of resize handles.
+    pub grip_length: f32,
+    /// The thickness of the grips in the center of resize handles.
+    pub grip_thickness: f32,
+    /// The thickness of the line previewing where a pane will be split.
+    pub split_preview: f32,
+    /// The length of the arrow previewing which pane will be joined.
+    pub join_arrow_length: f32,
+    /// The thickness of the shaft of the join arrow.
+    pub join_arrow_thickness: f32,
+    /// The size of the head of the join arrow.
+    pub join_arrow_head: f32,
+    /// The width of the logo at the start of the menu bar.
+    pub logo_width: f32,
+    /// The height of the logo at the start of the menu bar.
+    pub logo_height: f32,
+}
+
+impl Default for SizeTokens {
+    fn default() -> Self {
+        Self {
+            pane_header: 27.,
+            pane_footer: 22.,
+            menu_bar: 30.,
+            scrollbar: 6.,
+            pane_padding: 1.5,
+            resize_handle: 7.,
+            grip_length: 24.,
+            grip_thickness: 2.,
+            split_preview: 2.,
+            join_arrow_length: 60.,
+            join_arrow_thickness: 8.,
+            join_arrow_head: 28.,
+            logo_width: 30.,
+            logo_height: 20.,
+        }
+    }
+}
+
+/// The sizes of icons, in logical pixels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IconSizes {
+    /// Icons next to small text.
+    pub small: f32,
+    /// Icons in headers, menus and toolbars.
+    pub medium: f32,
+    /// Icons standing on their own, like in the asset browser.
+    pub large: f32,
+}
+
+impl Default for IconSizes {
+    fn default() -> Self {
+        Self {
+            small: 12.,
+            medium: 16.,
+            large: 24.,
+        }
+    }
+}
+
+/// How heavy the strokes of a font are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FontWeight {
+    /// Regular text.
+    Regular,
+    /// Slightly emphasized text, like labels.
+    Medium,
+    /// Headings and strong emphasis.
+    Bold,
+}
+
+/// A step of the typography scale.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TextToken {
+    /// The font size, in logical pixels.
+    pub size: f32,
+    /// The font weight.
+    pub weight: FontWeight,
+}
+
+impl TextToken {
+    const fn new(size: f32, weight: FontWeight) -> Self {
+        Self { size, weight }
+    }
+}
+
+/// The role of a piece of text, selecting its step of the [`Typography`] scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextRole {
+    /// Small annotations, like tooltips and status text.
+    Caption,
+    /// Regular text.
+    Body,
+    /// The titles of panes, buttons and menu entries.
+    Label,
+    /// The headings of sections.
+    Heading,
+    /// The titles of windows and dialogs.
+    Title,
+}
+
+/// The fonts used for each [`FontWeight`], the default font unless set.
+///
+/// Fonts are assets, so they are not part of theme files and are kept when switching themes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FontFamily {
+    /// The font of [`FontWeight::Regular`].
+    pub regular: Handle<Font>,
+    /// The font of [`FontWeight::Medium`].
+    pub medium: Handle<Font>,
+    /// The font of [`FontWeight::Bold`].
+    pub bold: Handle<Font>,
+}
+
+/// The typography scale of the editor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Typography {
+    /// See [`TextRole::Caption`].
+    pub caption: TextToken,
+    /// See [`TextRole::Body`].
+    pub body: TextToken,
+    /// See [`TextRole::Label`].
+    pub label: TextToken,
+    /// See [`TextRole::Heading`].
+    pub heading: TextToken,
+    /// See [`TextRole::Title`].
+    pub title: TextToken,
+    /// The fonts of the weights.
+    #[serde(skip)]
+    pub fonts: FontFamily,
+}
+
+impl Default for Typography {
+    fn default() -> Self {
+        Self {
+            caption: TextToken::new(12., FontWeight::Regular),
+            body: TextToken::new(14., FontWeight::Regular),
+            label: TextToken::new(14., FontWeight::Medium),
+            heading: TextToken::new(18., FontWeight::Bold),
+            title: TextToken::new(24., FontWeight::Bold),
+            fonts: default(),
+        }
+    }
+}
+
+impl Typography {
+    /// The step of the scale for text of the given role.
+    pub fn token(&self, role: TextRole) -> TextToken {
+        match role {
+            TextRole::Caption => self.caption,
+            TextRole::Body => self.body,
+            TextRole::Label => self.label,
+            TextRole::Heading => self.heading,
+            TextRole::Title => self.title,
+        }
+    }
+
+    /// The [`TextFont`] of text with the given role.
+    pub fn font(&self, role: TextRole) -> TextFont {
+        let token = self.token(role);
+        let font = match token.weight {
+            FontWeight::Regular => &self.fonts.regular,
+            FontWeight::Medium => &self.fonts.medium,
+            FontWeight::Bold => &self.fonts.bold,
+        };
+        TextFont {
+            font: font.clone(),
+            font_size: token.size,
+            ..default()
+        }
+    }
+}
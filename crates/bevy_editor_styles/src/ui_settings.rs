@@ -0,0 +1,185 @@
+//! The UI scale and density chosen by the user, to fit the editor to their display.
+
+use bevy::prelude::*;
+
+use crate::Theme;
+
+/// The smallest [`UiSettings::scale`].
+pub const MIN_UI_SCALE: f32 = 0.5;
+/// The largest [`UiSettings::scale`].
+pub const MAX_UI_SCALE: f32 = 3.;
+
+/// How tightly the editor UI is packed.
+#[derive(Reflect, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Density {
+    /// The spacings and sizes of the theme files.
+    #[default]
+    Comfortable,
+    /// Smaller spacings, bars and headers, to fit more on small displays.
+    Compact,
+}
+
+impl Density {
+    /// The factor the spacing scale is multiplied with.
+    fn spacing_factor(self) -> f32 {
+        match self {
+            Self::Comfortable => 1.,
+            Self::Compact => 0.6,
+        }
+    }
+
+    /// The factor the sizes of bars, headers and scrollbars are multiplied with.
+    fn size_factor(self) -> f32 {
+        match self {
+            Self::Comfortable => 1.,
+            Self::Compact => 0.8,
+        }
+    }
+}
+
+/// The UI scale and density of the editor.
+///
+/// The editor stores them in the `[ui]` section of the user settings.
+/// Changing them restyles the [`Theme`] and sets the [`UiScale`].
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq)]
+pub struct UiSettings {
+    /// Scales the whole UI, on top of the scale factor of the window.
+    /// Clamped between [`MIN_UI_SCALE`] and [`MAX_UI_SCALE`].
+    pub scale: f32,
+    /// How tightly the UI is packed.
+    pub density: Density,
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        Self {
+            scale: 1.,
+            density: Density::Comfortable,
+        }
+    }
+}
+
+impl Theme {
+    /// A copy of the theme with its spacings and sizes adjusted to `density`.
+    ///
+    /// Themes are written for [`Density::Comfortable`], which leaves them as they are.
+    /// For other densities the sizes are rounded to whole pixels.
+    pub fn with_density(&self, density: Density) -> Theme {
+        let mut theme = self.clone();
+        if density == Density::Comfortable {
+            return theme;
+        }
+        let spacing = &mut theme.spacing;
+        for value in [
+            &mut spacing.xs,
+            &mut spacing.sm,
+            &mut spacing.md,
+            &mut spacing.lg,
+            &mut spacing.xl,
+        ] {
+            *value = (*value * density.spacing_factor()).round();
+        }
+        let sizes = &mut theme.sizes;
+        for value in [
+            &mut sizes.pane_header,
+            &mut sizes.pane_footer,
+            &mut sizes.menu_bar,
+            &mut sizes.scrollbar,
+            &mut sizes.pane_padding,
+            &mut sizes.resize_handle,
+            &mut sizes.grip_length,
+            &mut sizes.grip_thickness,
+            &mut sizes.split_preview,
+            &mut sizes.join_arrow_length,
+            &mut sizes.join_arrow_thickness,
+            &mut sizes.join_arrow_head,
+            &mut sizes.logo_width,
+            &mut sizes.logo_height,
+        ] {
+            *value = (*value * density.size_factor()).round();
+        }
+        theme
+    }
+}
+
+/// Sets the [`UiScale`] from the [`UiSettings`].
+pub(crate) fn apply_ui_scale(settings: Res<UiSettings>, mut ui_scale: ResMut<UiScale>) {
+    let scale = settings.scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+    if ui_scale.0 != scale {
+        ui_scale.0 = scale;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SetTheme;
+
+    #[test]
+    fn compact_density() {
+        let theme = Theme::default();
+        assert_eq!(theme.with_density(Density::Comfortable), theme);
+
+        let compact = theme.with_density(Density::Compact);
+        assert!(compact.spacing.md < theme.spacing.md);
+        assert!(compact.sizes.pane_header < theme.sizes.pane_header);
+        assert!(compact.sizes.join_arrow_length < theme.sizes.join_arrow_length);
+        assert!(compact.sizes.resize_handle < theme.sizes.resize_handle);
+        assert_eq!(compact.sizes.pane_header, compact.sizes.pane_header.round());
+        assert_eq!(compact.colors, theme.colors);
+        assert_eq!(compact.typography, theme.typography);
+    }
+
+    #[test]
+    fn change_at_runtime() {
+        let mut app = App::new();
+        app.add_plugins(crate::StylesPlugin);
+        app.update();
+        assert_eq!(*app.world().resource::<Theme>(), Theme::default());
+        assert_eq!(app.world().resource::<UiScale>().0, 1.);
+
+        app.insert_resource(UiSettings {
+            scale: 1.5,
+            density: Density::Compact,
+        });
+        app.update();
+        assert_eq!(app.world().resource::<UiScale>().0, 1.5);
+        assert_eq!(
+            *app.world().resource::<Theme>(),
+            Theme::default().with_density(Density::Compact)
+        );
+
+        // Switching themes keeps the density
+        app.world_mut().send_event(SetTheme("Light".to_string()));
+        app.update();
+        let theme = app.world().resource::<Theme>();
+        assert_eq!(theme.name, "Light");
+        assert_eq!(
+            theme.sizes.pane_header,
+            Theme::default()
+                .with_density(Density::Compact)
+                .sizes
+                .pane_header
+        );
+
+        app.world_mut().resource_mut::<UiSettings>().scale = 10.;
+        app.update();
+        assert_eq!(app.world().resource::<UiScale>().0, MAX_UI_SCALE);
+    }
+
+    #[test]
+    fn settings_loaded_at_startup() {
+        let mut app = App::new();
+        app.insert_resource(UiSettings {
+            scale: 2.,
+            density: Density::Compact,
+        });
+        app.add_plugins(crate::StylesPlugin);
+        app.update();
+        assert_eq!(app.world().resource::<UiScale>().0, 2.);
+        assert_eq!(
+            *app.world().resource::<Theme>(),
+            Theme::default().with_density(Density::Compact)
+        );
+    }
+}
@@ -0,0 +1,485 @@
+//! Minimum pane sizes and collapsing panes down to their header.
+
+use bevy::{
+    ecs::system::{SystemParam, SystemState},
+    prelude::*,
+};
+use bevy_editor_styles::Theme;
+
+use crate::{
+    layout::sized_children, ui::collapsed_pane_size, Divider, DragState, PaneAreaNode,
+    PaneHeaderNode, PaneRootNode, Size,
+};
+
+/// Marker for panes that are collapsed to a header-only strip, because they don't have room for their minimum height.
+///
+/// Only panes within a [vertical](Divider::Vertical) divider collapse, as their header spans their width.
+/// Clicking the header of a collapsed pane expands it again.
+#[derive(Component)]
+pub struct PaneCollapsed;
+
+/// The preferred minimum size of a pane in logical pixels, as registered in the [`PaneRegistry`](crate::PaneRegistry).
+#[derive(Component, Clone, Copy, Default)]
+pub(crate) struct PaneMinSize(pub(crate) Vec2);
+
+/// Computes how small elements of the layout can get along an axis.
+#[derive(SystemParam)]
+pub(crate) struct MinSizes<'w, 's> {
+    theme: Res<'w, Theme>,
+    divider_query: Query<'w, 's, &'static Divider>,
+    children_query: Query<'w, 's, &'static Children>,
+    pane_query:
+        Query<'w, 's, (Has<PaneCollapsed>, Option<&'static PaneMinSize>), With<PaneRootNode>>,
+}
+
+impl MinSizes<'_, '_> {
+    /// The size `entity` can be squashed to along `axis`, panes can get as small as their header.
+    pub(crate) fn squashed(&self, entity: Entity, axis: Divider) -> f32 {
+        let Ok(divider) = self.divider_query.get(entity) else {
+            return collapsed_pane_size(&self.theme);
+        };
+
+        let children = self
+            .children_query
+            .get(entity)
+            .into_iter()
+            .flatten()
+            .filter(|child| {
+                self.divider_query.contains(**child) || self.pane_query.contains(**child)
+            })
+            .map(|child| self.squashed(*child, axis));
+
+        if *divider == axis {
+            children.sum()
+        } else {
+            children.fold(0., f32::max)
+        }
+    }
+
+    /// The size `entity` would like to have at least along `axis`.
+    pub(crate) fn preferred(&self, entity: Entity, axis: Divider) -> f32 {
+        match self.pane_query.get(entity) {
+            Ok((true, _)) => collapsed_pane_size(&self.theme),
+            Ok((false, min_size)) => axis
+                .main_axis(min_size.map_or(Vec2::ZERO, |min_size| min_size.0))
+                .max(collapsed_pane_size(&self.theme)),
+            Err(_) => self.squashed(entity, axis),
+        }
+    }
+}
+
+/// Collapses panes that have less room than their minimum height within vertical dividers.
+/// Collapsed panes are kept at the size of their header as the window resizes,
+/// and expand again once they are dragged open past their minimum size.
+pub(crate) fn update_collapsed_panes(
+    mut commands: Commands,
+    drag_state: Res<DragState>,
+    theme: Res<Theme>,
+    divider_query: Query<(&Divider, &Node, &Children)>,
+    pane_query: Query<(Has<PaneCollapsed>, Option<&PaneMinSize>), With<PaneRootNode>>,
+    mut size_query: Query<&mut Size>,
+) {
+    // Let the user drag freely, the result is applied once the drag ends
+    if drag_state.is_dragging {
+        return;
+    }
+
+    let collapsed_size = collapsed_pane_size(&theme);
+    for (divider, node, children) in &divider_query {
+        if *divider != Divider::Vertical {
+            // Panes moved next to each other can not stay collapsed to their header
+            for child in children.iter() {
+                if pane_query.get(*child).is_ok_and(|(collapsed, _)| collapsed) {
+                    commands.entity(*child).remove::<PaneCollapsed>();
+                }
+            }
+            continue;
+        }
+
+        let parent_size = divider.main_axis(node.size());
+        // The layout has not been computed yet
+        if parent_size <= 0. {
+            continue;
+        }
+
+        let (entities, before): (Vec<Entity>, Vec<Sibling>) = sized_children(children, |child| {
+            size_query.get(child).ok().map(|size| size.0)
+        })
+        .map(|(entity, size)| {
+            let pane = pane_query.get(entity).ok();
+            let sibling = Sibling {
+                size,
+                min_size: pane.map(|(_, min_size)| {
+                    divider.main_axis(min_size.map_or(Vec2::ZERO, |min_size| min_size.0))
+                }),
+                collapsed: pane.is_some_and(|(collapsed, _)| collapsed),
+            };
+            (entity, sibling)
+        })
+        .unzip();
+
+        let mut after = before.clone();
+        collapse_siblings(&mut after, parent_size, collapsed_size);
+
+        for ((entity, before), after) in entities.into_iter().zip(before).zip(after) {
+            if after.collapsed && !before.collapsed {
+                commands.entity(entity).insert(PaneCollapsed);
+            } else if !after.collapsed && before.collapsed {
+                commands.entity(entity).remove::<PaneCollapsed>();
+            }
+            if after.size != before.size {
+                size_query.get_mut(entity).unwrap().0 = after.size;
+            }
+        }
+    }
+}
+
+/// A child of a vertical divider, as seen by [`collapse_siblings`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Sibling {
+    /// The share of the divider.
+    size: f32,
+    /// The minimum height of a pane in logical pixels, `None` for nested dividers.
+    min_size: Option<f32>,
+    collapsed: bool,
+}
+
+/// Collapses the siblings that are smaller than their minimum size, and expands those dragged open again.
+///
+/// Collapsed siblings snap to `collapsed_size`, the closest expanded sibling absorbs the difference.
+fn collapse_siblings(siblings: &mut [Sibling], parent_size: f32, collapsed_size: f32) {
+    for index in 0..siblings.len() {
+        let Sibling {
+            size,
+            min_size: Some(min_size),
+            collapsed,
+        } = siblings[index]
+        else {
+            continue;
+        };
+        let fits = size * parent_size >= min_size - 0.5;
+
+        if collapsed {
+            // Only expand once the pane is dragged open past its header
+            if fits && size * parent_size > collapsed_size + 0.5 {
+                siblings[index].collapsed = false;
+                continue;
+            }
+        } else if fits || min_size <= collapsed_size {
+            continue;
+        } else {
+            siblings[index].collapsed = true;
+        }
+
+        let Some(neighbor) = closest_expanded(index, siblings) else {
+            continue;
+        };
+        let delta = (size - collapsed_size / parent_size).max(-siblings[neighbor].size);
+        if delta.abs() < 1e-4 {
+            continue;
+        }
+        siblings[index].size -= delta;
+        siblings[neighbor].size += delta;
+    }
+}
+
+/// Finds the sibling closest to `index` that is not collapsed, preferring the next one.
+fn closest_expanded(index: usize, siblings: &[Sibling]) -> Option<usize> {
+    (1..siblings.len())
+        .flat_map(|distance| [index.checked_add(distance), index.checked_sub(distance)])
+        .flatten()
+        .find(|i| siblings.get(*i).is_some_and(|sibling| !sibling.collapsed))
+}
+
+/// Hides everything but the header of collapsed panes.
+pub(crate) fn apply_collapsed_display(
+    area_query: Query<(&Parent, &Children), With<PaneAreaNode>>,
+    header_query: Query<(), With<PaneHeaderNode>>,
+    collapsed_query: Query<(), With<PaneCollapsed>>,
+    mut style_query: Query<&mut Style>,
+) {
+    for (root, children) in &area_query {
+        let display = if collapsed_query.contains(root.get()) {
+            Display::None
+        } else {
+            Display::Flex
+        };
+
+        for child in children
+            .iter()
+            .filter(|child| !header_query.contains(**child))
+        {
+            if let Ok(mut style) = style_query.get_mut(*child) {
+                if style.display != display {
+                    style.display = display;
+                }
+            }
+        }
+    }
+}
+
+/// Collapses `pane` to its header, the size is adjusted by [`update_collapsed_panes`].
+pub(crate) fn collapse_pane(world: &mut World, pane: Entity) {
+    let in_vertical_divider = world
+        .get::<Parent>(pane)
+        .and_then(|parent| world.get::<Divider>(parent.get()))
+        .is_some_and(|divider| *divider == Divider::Vertical);
+
+    if world.get::<PaneRootNode>(pane).is_none() || !in_vertical_divider {
+        warn!("Can not collapse {pane}, it is not a pane within a vertical divider");
+        return;
+    }
+
+    world.entity_mut(pane).insert(PaneCollapsed);
+}
+
+/// Expands a collapsed pane back to its minimum size, taking the room from its siblings.
+pub(crate) fn expand_pane(world: &mut World, pane: Entity) {
+    if world.get::<PaneCollapsed>(pane).is_none() {
+        return;
+    }
+
+    let Some(parent) = world.get::<Parent>(pane).map(Parent::get) else {
+        world.entity_mut(pane).remove::<PaneCollapsed>();
+        return;
+    };
+    let (Some(&divider), Some(node)) = (world.get::<Divider>(parent), world.get::<Node>(parent))
+    else {
+        // Outside of a divider the pane already has all the room there is
+        world.entity_mut(pane).remove::<PaneCollapsed>();
+        return;
+    };
+
+    let parent_size = divider.main_axis(node.size());
+    if parent_size <= 0. {
+        return;
+    }
+
+    let mut state = SystemState::<(MinSizes, Query<&Size>, Query<&Children>)>::new(world);
+    let (min_sizes, size_query, children_query) = state.get(world);
+
+    let min_size = world
+        .get::<PaneMinSize>(pane)
+        .map_or(0., |min_size| divider.main_axis(min_size.0));
+    let required = min_size / parent_size - size_query.get(pane).map_or(0., |size| size.0);
+
+    // Siblings with room to spare
+    let donors: Vec<(Entity, f32)> = children_query
+        .get(parent)
+        .into_iter()
+        .flatten()
+        .filter(|child| **child != pane)
+        .filter_map(|child| {
+            let size = size_query.get(*child).ok()?.0;
+            Some((
+                *child,
+                size - min_sizes.preferred(*child, divider) / parent_size,
+            ))
+        })
+        .collect();
+
+    let Some(taken) = take_room(required, donors) else {
+        warn!("Not enough room to expand {pane}");
+        return;
+    };
+    for (donor, taken) in taken {
+        world.get_mut::<Size>(donor).unwrap().0 -= taken;
+    }
+
+    world.get_mut::<Size>(pane).unwrap().0 += required.max(0.);
+    world.entity_mut(pane).remove::<PaneCollapsed>();
+}
+
+/// Splits the `required` room between the `donors` with room to spare, roomiest first.
+///
+/// Returns how much to take from each donor, or `None` if they don't have enough room together.
+fn take_room(required: f32, mut donors: Vec<(Entity, f32)>) -> Option<Vec<(Entity, f32)>> {
+    donors.retain(|(_, spare)| *spare > 0.);
+    donors.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    if donors.iter().map(|(_, spare)| spare).sum::<f32>() < required {
+        return None;
+    }
+
+    let mut remaining = required.max(0.);
+    Some(
+        donors
+            .into_iter()
+            .map_while(|(donor, spare)| {
+                let taken = spare.min(remaining);
+                remaining -= taken;
+                (taken > 0.).then_some((donor, taken))
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{apply, pane, setup_app},
+        PaneLayoutCommandsExt, PaneStructure,
+    };
+
+    const COLLAPSED: f32 = 30.;
+
+    fn expanded(size: f32, min_size: f32) -> Sibling {
+        Sibling {
+            size,
+            min_size: Some(min_size),
+            collapsed: false,
+        }
+    }
+
+    fn collapsed(size: f32, min_size: f32) -> Sibling {
+        Sibling {
+            collapsed: true,
+            ..expanded(size, min_size)
+        }
+    }
+
+    fn assert_sizes(siblings: &[Sibling], expected: &[f32]) {
+        let sizes: Vec<f32> = siblings.iter().map(|sibling| sibling.size).collect();
+        assert!(
+            sizes
+                .iter()
+                .zip(expected)
+                .all(|(size, expected)| (size - expected).abs() < 1e-5),
+            "expected sizes {expected:?}, got {sizes:?}"
+        );
+    }
+
+    #[test]
+    fn collapse_when_too_small() {
+        // 100px of 1000px for a pane that needs 150px
+        let mut siblings = [expanded(0.1, 150.), expanded(0.9, 150.)];
+        collapse_siblings(&mut siblings, 1000., COLLAPSED);
+
+        assert!(siblings[0].collapsed);
+        assert!(!siblings[1].collapsed);
+        assert_sizes(&siblings, &[0.03, 0.97]);
+    }
+
+    #[test]
+    fn keep_panes_that_fit() {
+        let mut siblings = [expanded(0.2, 150.), expanded(0.8, 150.)];
+        collapse_siblings(&mut siblings, 1000., COLLAPSED);
+        assert_eq!(siblings, [expanded(0.2, 150.), expanded(0.8, 150.)]);
+
+        // Panes that could not get any smaller than their header don't collapse
+        let mut siblings = [expanded(0.01, 20.), expanded(0.99, 150.)];
+        collapse_siblings(&mut siblings, 1000., COLLAPSED);
+        assert_eq!(siblings, [expanded(0.01, 20.), expanded(0.99, 150.)]);
+
+        // Nested dividers are resized but never collapse
+        let mut siblings = [
+            Sibling {
+                size: 0.01,
+                min_size: None,
+                collapsed: false,
+            },
+            expanded(0.99, 150.),
+        ];
+        collapse_siblings(&mut siblings, 1000., COLLAPSED);
+        assert!(!siblings[0].collapsed);
+        assert_sizes(&siblings, &[0.01, 0.99]);
+    }
+
+    #[test]
+    fn expand_when_dragged_open() {
+        let mut siblings = [collapsed(0.5, 150.), expanded(0.5, 150.)];
+        collapse_siblings(&mut siblings, 1000., COLLAPSED);
+        assert_eq!(siblings, [expanded(0.5, 150.), expanded(0.5, 150.)]);
+
+        // Dragged open, but not past the minimum size
+        let mut siblings = [collapsed(0.1, 150.), expanded(0.9, 150.)];
+        collapse_siblings(&mut siblings, 1000., COLLAPSED);
+        assert!(siblings[0].collapsed);
+        assert_sizes(&siblings, &[0.03, 0.97]);
+    }
+
+    #[test]
+    fn snap_to_header_on_resize() {
+        // The divider grew from 1000px to 2000px, the collapsed pane keeps the size of its header
+        let mut siblings = [
+            expanded(0.485, 150.),
+            collapsed(0.03, 150.),
+            expanded(0.485, 150.),
+        ];
+        collapse_siblings(&mut siblings, 2000., COLLAPSED);
+
+        assert!(siblings[1].collapsed);
+        assert_sizes(&siblings, &[0.485, 0.015, 0.5]);
+    }
+
+    #[test]
+    fn absorb_in_closest_expanded() {
+        let siblings = [
+            expanded(0.25, 0.),
+            collapsed(0.25, 0.),
+            expanded(0.25, 0.),
+            collapsed(0.25, 0.),
+        ];
+        assert_eq!(closest_expanded(0, &siblings), Some(2));
+        assert_eq!(closest_expanded(1, &siblings), Some(2));
+        assert_eq!(closest_expanded(3, &siblings), Some(2));
+
+        let siblings = [collapsed(0.5, 0.), collapsed(0.5, 0.)];
+        assert_eq!(closest_expanded(0, &siblings), None);
+
+        // Without an expanded sibling, panes that don't fit keep their size
+        let mut siblings = [collapsed(0.5, 600.), collapsed(0.5, 600.)];
+        collapse_siblings(&mut siblings, 1000., COLLAPSED);
+        assert_eq!(siblings, [collapsed(0.5, 600.), collapsed(0.5, 600.)]);
+    }
+
+    #[test]
+    fn take_room_from_roomiest() {
+        let [a, b, c] = [0, 1, 2].map(Entity::from_raw);
+
+        let taken = take_room(0.3, vec![(a, 0.1), (b, 0.25), (c, -0.1)]).unwrap();
+        assert_eq!(taken.len(), 2);
+        assert_eq!(taken[0], (b, 0.25));
+        assert_eq!(taken[1].0, a);
+        assert!((taken[1].1 - 0.05).abs() < 1e-5);
+
+        assert_eq!(
+            take_room(0.1, vec![(a, 0.2), (b, 0.1)]),
+            Some(vec![(a, 0.1)])
+        );
+        assert_eq!(take_room(0.5, vec![(a, 0.2), (b, 0.1)]), None);
+    }
+
+    #[test]
+    fn collapse_only_in_vertical_dividers() {
+        let mut app = setup_app();
+        let scene_tree = pane(&mut app, "Scene Tree");
+        let viewport = pane(&mut app, "Viewport 3D");
+
+        // The viewport is next to the left column, the scene tree on top of the properties
+        apply(&mut app, |commands| {
+            commands.collapse_pane(scene_tree);
+            commands.collapse_pane(viewport);
+        });
+        assert!(app.world().get::<PaneCollapsed>(scene_tree).is_some());
+        assert!(app.world().get::<PaneCollapsed>(viewport).is_none());
+
+        // Only the header is shown
+        let structure = *app.world().get::<PaneStructure>(scene_tree).unwrap();
+        for child in app.world().get::<Children>(structure.area).unwrap() {
+            let expected = if *child == structure.header {
+                Display::Flex
+            } else {
+                Display::None
+            };
+            assert_eq!(app.world().get::<Style>(*child).unwrap().display, expected);
+        }
+
+        // Collapsed panes that end up next to each other are expanded
+        app.world_mut().entity_mut(viewport).insert(PaneCollapsed);
+        app.update();
+        app.update();
+        assert!(app.world().get::<PaneCollapsed>(viewport).is_none());
+    }
+}
@@ -0,0 +1,473 @@
+//! Joining and splitting panes by dragging from the corners of their area, like Blender's area action zones.
+//!
+//! Dragging from a corner into the pane itself splits it at the pointer,
+//! dragging into a neighboring pane of the same divider joins that neighbor into the pane.
+
+use std::f32::consts::FRAC_PI_4;
+
+use bevy::{prelude::*, window::SystemCursorIcon, winit::cursor::CursorIcon};
+use bevy_editor_styles::Theme;
+
+use crate::{layout::can_join, Divider, PaneLayoutCommandsExt, PaneRootNode};
+
+/// The size of the corner hotspots of a pane.
+const CORNER_SIZE: f32 = 12.;
+/// How far the pointer has to travel before a corner drag does anything.
+const DRAG_THRESHOLD: f32 = 10.;
+
+/// The state of an ongoing corner drag.
+#[derive(Resource, Default)]
+pub(crate) struct CornerDragState {
+    /// The pane the drag started from.
+    source: Option<Entity>,
+    /// What will happen when the drag ends.
+    action: Option<CornerAction>,
+    /// The node previewing the action.
+    overlay: Option<Entity>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CornerAction {
+    /// Join `target` into the source pane.
+    Join {
+        target: Entity,
+        /// The direction of the divider both panes are in.
+        direction: Divider,
+        /// Whether `target` comes after the source pane.
+        forward: bool,
+    },
+    /// Split the source pane.
+    Split { direction: Divider, ratio: f32 },
+}
+
+/// Adds the corner hotspots to a pane root.
+pub(crate) fn spawn_pane_corners(commands: &mut Commands, pane_root: Entity) {
+    let corners = [
+        (Val::Px(0.), Val::Auto, Val::Px(0.), Val::Auto),
+        (Val::Auto, Val::Px(0.), Val::Px(0.), Val::Auto),
+        (Val::Px(0.), Val::Auto, Val::Auto, Val::Px(0.)),
+        (Val::Auto, Val::Px(0.), Val::Auto, Val::Px(0.)),
+    ];
+
+    for (left, right, top, bottom) in corners {
+        commands
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left,
+                    right,
+                    top,
+                    bottom,
+                    width: Val::Px(CORNER_SIZE),
+                    height: Val::Px(CORNER_SIZE),
+                    ..default()
+                },
+                z_index: ZIndex(2),
+                ..default()
+            })
+            .observe(on_corner_drag_start)
+            .observe(on_corner_drag)
+            .observe(on_corner_drag_end)
+            .observe(
+                |_trigger: Trigger<Pointer<Cancel>>,
+                 mut commands: Commands,
+                 mut state: ResMut<CornerDragState>| {
+                    reset(&mut commands, &mut state);
+                },
+            )
+            .observe(
+                |_trigger: Trigger<Pointer<Move>>,
+                 window_query: Query<Entity, With<Window>>,
+                 mut commands: Commands| {
+                    let window = window_query.single();
+                    commands
+                        .entity(window)
+                        .insert(CursorIcon::System(SystemCursorIcon::Crosshair));
+                },
+            )
+            .observe(
+                |_trigger: Trigger<Pointer<Out>>,
+                 window_query: Query<Entity, With<Window>>,
+                 mut commands: Commands| {
+                    let window = window_query.single();
+                    commands
+                        .entity(window)
+                        .insert(CursorIcon::System(SystemCursorIcon::Default));
+                },
+            )
+            .set_parent(pane_root);
+    }
+}
+
+fn on_corner_drag_start(
+    trigger: Trigger<Pointer<DragStart>>,
+    mut commands: Commands,
+    mut state: ResMut<CornerDragState>,
+    parent_query: Query<&Parent>,
+) {
+    reset(&mut commands, &mut state);
+    if trigger.event().button != PointerButton::Primary {
+        return;
+    }
+
+    state.source = parent_query.get(trigger.entity()).ok().map(Parent::get);
+}
+
+#[expect(clippy::too_many_arguments)]
+fn on_corner_drag(
+    trigger: Trigger<Pointer<Drag>>,
+    mut commands: Commands,
+    mut state: ResMut<CornerDragState>,
+    theme: Res<Theme>,
+    pane_query: Query<(Entity, &Node, &GlobalTransform), With<PaneRootNode>>,
+    parent_query: Query<&Parent>,
+    children_query: Query<&Children>,
+    divider_query: Query<&Divider>,
+) {
+    let Some(source) = state.source else {
+        return;
+    };
+
+    let event = trigger.event();
+    let action = (event.distance.length() >= DRAG_THRESHOLD)
+        .then(|| {
+            corner_action(
+                source,
+                event.pointer_location.position,
+                event.distance,
+                &pane_query,
+                &parent_query,
+                &children_query,
+                &divider_query,
+            )
+        })
+        .flatten();
+
+    if action == state.action {
+        return;
+    }
+
+    if let Some(overlay) = state.overlay.take() {
+        commands.entity(overlay).despawn_recursive();
+    }
+    state.overlay = action.map(|action| spawn_overlay(&mut commands, &theme, source, action));
+    state.action = action;
+}
+
+fn on_corner_drag_end(
+    _trigger: Trigger<Pointer<DragEnd>>,
+    mut commands: Commands,
+    mut state: ResMut<CornerDragState>,
+    pane_query: Query<&PaneRootNode>,
+) {
+    if let (Some(source), Some(action)) = (state.source, state.action) {
+        match action {
+            CornerAction::Join { target, .. } => commands.join_panes(source, target),
+            CornerAction::Split { direction, ratio } => {
+                if let Ok(pane) = pane_query.get(source) {
+                    commands.split_pane(source, direction, ratio, pane.name.clone());
+                }
+            }
+        }
+    }
+
+    reset(&mut commands, &mut state);
+}
+
+/// Clears the drag state and removes the preview.
+fn reset(commands: &mut Commands, state: &mut CornerDragState) {
+    if let Some(overlay) = state.overlay.take() {
+        commands.entity(overlay).despawn_recursive();
+    }
+    *state = CornerDragState::default();
+}
+
+fn pane_rect(node: &Node, transform: &GlobalTransform) -> Rect {
+    Rect::from_center_size(transform.translation().truncate(), node.size())
+}
+
+/// Works out what releasing the pointer at `position` would do.
+fn corner_action(
+    source: Entity,
+    position: Vec2,
+    distance: Vec2,
+    pane_query: &Query<(Entity, &Node, &GlobalTransform), With<PaneRootNode>>,
+    parent_query: &Query<&Parent>,
+    children_query: &Query<&Children>,
+    divider_query: &Query<&Divider>,
+) -> Option<CornerAction> {
+    let (_, node, transform) = pane_query.get(source).ok()?;
+    let source_rect = pane_rect(node, transform);
+
+    if source_rect.contains(position) {
+        return Some(split_action(source_rect, position, distance));
+    }
+
+    let (target, ..) = pane_query.iter().find(|(entity, node, transform)| {
+        *entity != source && pane_rect(node, transform).contains(position)
+    })?;
+    join_action(source, target, parent_query, children_query, divider_query)
+}
+
+/// Dragging into the pane itself splits it at `position`, perpendicular to the drag.
+fn split_action(source_rect: Rect, position: Vec2, distance: Vec2) -> CornerAction {
+    let direction = if distance.x.abs() > distance.y.abs() {
+        Divider::Horizontal
+    } else {
+        Divider::Vertical
+    };
+    let ratio = direction
+        .main_axis((position - source_rect.min) / source_rect.size())
+        .clamp(0.1, 0.9);
+    CornerAction::Split { direction, ratio }
+}
+
+/// Dragging into a neighbor joins it, as long as they share a whole edge.
+fn join_action(
+    source: Entity,
+    target: Entity,
+    parent_query: &Query<&Parent>,
+    children_query: &Query<&Children>,
+    divider_query: &Query<&Divider>,
+) -> Option<CornerAction> {
+    let slot = |pane: Entity| {
+        let parent = parent_query.get(pane).ok()?.get();
+        let index = children_query
+            .get(parent)
+            .ok()?
+            .iter()
+            .position(|entity| *entity == pane)?;
+        Some((parent, index))
+    };
+    let (source_slot, target_slot) = (slot(source)?, slot(target)?);
+    if !can_join(source_slot, target_slot) {
+        return None;
+    }
+
+    Some(CornerAction::Join {
+        target,
+        direction: *divider_query.get(source_slot.0).ok()?,
+        forward: target_slot.1 > source_slot.1,
+    })
+}
+
+/// Spawns the preview of a corner action: a line where the pane will be split,
+/// or an arrow over the neighbor that will be absorbed.
+fn spawn_overlay(
+    commands: &mut Commands,
+    theme: &Theme,
+    source: Entity,
+    action: CornerAction,
+) -> Entity {
+    match action {
+        CornerAction::Split { direction, ratio } => {
+            let thickness = Val::Px(theme.sizes.split_preview);
+            let style = match direction {
+                Divider::Horizontal => Style {
+                    left: Val::Percent(ratio * 100.),
+                    width: thickness,
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                Divider::Vertical => Style {
+                    top: Val::Percent(ratio * 100.),
+                    width: Val::Percent(100.),
+                    height: thickness,
+                    ..default()
+                },
+            };
+
+            commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        ..style
+                    },
+                    background_color: BackgroundColor(theme.colors.text.primary),
+                    z_index: ZIndex(10),
+                    ..default()
+                })
+                .set_parent(source)
+                .id()
+        }
+        CornerAction::Join {
+            target,
+            direction,
+            forward,
+        } => {
+            // The arrow points from the source pane into the absorbed pane
+            let flex_direction = match (direction, forward) {
+                (Divider::Horizontal, true) => FlexDirection::Row,
+                (Divider::Horizontal, false) => FlexDirection::RowReverse,
+                (Divider::Vertical, true) => FlexDirection::Column,
+                (Divider::Vertical, false) => FlexDirection::ColumnReverse,
+            };
+            let sizes = &theme.sizes;
+            let (length, thickness) = (
+                Val::Px(sizes.join_arrow_length),
+                Val::Px(sizes.join_arrow_thickness),
+            );
+            let (shaft_width, shaft_height) = match direction {
+                Divider::Horizontal => (length, thickness),
+                Divider::Vertical => (thickness, length),
+            };
+
+            commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        flex_direction,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: BackgroundColor(theme.colors.scrim),
+                    z_index: ZIndex(10),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // Shaft
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            width: shaft_width,
+                            height: shaft_height,
+                            ..default()
+                        },
+                        background_color: BackgroundColor(theme.colors.text.primary),
+                        ..default()
+                    });
+                    // Head, a square turned on its corner that overlaps the end of the shaft
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(sizes.join_arrow_head),
+                            height: Val::Px(sizes.join_arrow_head),
+                            margin: UiRect::all(Val::Px(-0.75 * sizes.join_arrow_thickness)),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(theme.colors.text.primary),
+                        transform: Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_4)),
+                        ..default()
+                    });
+                })
+                .set_parent(target)
+                .id()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+    use crate::{
+        testing::{apply, pane, setup_app},
+        LayoutPresets, PaneLayout,
+    };
+
+    #[test]
+    fn split_at_pointer() {
+        let rect = Rect::new(100., 100., 300., 200.);
+
+        // Dragging sideways splits into areas next to each other
+        assert_eq!(
+            split_action(rect, Vec2::new(150., 150.), Vec2::new(-20., 5.)),
+            CornerAction::Split {
+                direction: Divider::Horizontal,
+                ratio: 0.25,
+            }
+        );
+        assert_eq!(
+            split_action(rect, Vec2::new(150., 175.), Vec2::new(5., 20.)),
+            CornerAction::Split {
+                direction: Divider::Vertical,
+                ratio: 0.75,
+            }
+        );
+
+        // Splits leave room on both sides
+        assert_eq!(
+            split_action(rect, Vec2::new(101., 150.), Vec2::new(-20., 0.)),
+            CornerAction::Split {
+                direction: Divider::Horizontal,
+                ratio: 0.1,
+            }
+        );
+        assert_eq!(
+            split_action(rect, Vec2::new(150., 199.), Vec2::new(0., 20.)),
+            CornerAction::Split {
+                direction: Divider::Vertical,
+                ratio: 0.9,
+            }
+        );
+    }
+
+    #[test]
+    fn join_neighbors() {
+        let mut app = setup_app();
+        app.world_mut().resource_mut::<LayoutPresets>().register(
+            "Row",
+            PaneLayout::horizontal([
+                (1., PaneLayout::pane("Scene Tree")),
+                (1., PaneLayout::pane("Properties")),
+                (1., PaneLayout::pane("Viewport 3D")),
+            ]),
+        );
+
+        let scene_tree = pane(&mut app, "Scene Tree");
+        let properties = pane(&mut app, "Properties");
+        let viewport = pane(&mut app, "Viewport 3D");
+        let mut state = SystemState::<(Query<&Parent>, Query<&Children>, Query<&Divider>)>::new(
+            app.world_mut(),
+        );
+
+        // The scene tree is on top of the properties, next to the viewport
+        let (parent_query, children_query, divider_query) = state.get(app.world());
+        let join = |source, target| {
+            join_action(
+                source,
+                target,
+                &parent_query,
+                &children_query,
+                &divider_query,
+            )
+        };
+        assert_eq!(
+            join(scene_tree, properties),
+            Some(CornerAction::Join {
+                target: properties,
+                direction: Divider::Vertical,
+                forward: true,
+            })
+        );
+        assert_eq!(join(scene_tree, viewport), None);
+
+        apply(&mut app, |commands| commands.apply_layout_preset("Row"));
+        let scene_tree = pane(&mut app, "Scene Tree");
+        let properties = pane(&mut app, "Properties");
+        let viewport = pane(&mut app, "Viewport 3D");
+
+        let (parent_query, children_query, divider_query) = state.get(app.world());
+        let join = |source, target| {
+            join_action(
+                source,
+                target,
+                &parent_query,
+                &children_query,
+                &divider_query,
+            )
+        };
+        assert_eq!(
+            join(viewport, properties),
+            Some(CornerAction::Join {
+                target: properties,
+                direction: Divider::Horizontal,
+                forward: false,
+            })
+        );
+        // Only direct neighbors share a whole edge
+        assert_eq!(join(scene_tree, viewport), None);
+    }
+}
@@ -0,0 +1,286 @@
+//! Tracking the focused pane and moving focus between panes with the keyboard.
+//!
+//! - Hovering or clicking a pane focuses it.
+//! - [`MovePaneFocus`] cycles through the panes, or moves the focus to the closest pane in a
+//!   direction. The editor sends it from the key bindings of the `pane.focus_*` actions.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_editor_styles::Theme;
+
+use crate::{
+    layout::sized_children, Divider, PaneAreaNode, PaneRootNode, RootPaneLayoutNode, Size,
+};
+
+/// The pane that has the focus, keyboard shortcuts should be routed to this pane.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FocusedPane(pub Option<Entity>);
+
+impl FocusedPane {
+    /// Returns the focused pane, if any.
+    pub fn get(&self) -> Option<Entity> {
+        self.0
+    }
+}
+
+/// Focuses the pane when the pointer moves over it.
+pub(crate) fn on_pane_hover(trigger: Trigger<Pointer<Over>>, mut focused: ResMut<FocusedPane>) {
+    focused.set_if_neq(FocusedPane(Some(trigger.entity())));
+}
+
+/// Focuses the pane when it is pressed.
+pub(crate) fn on_pane_pressed(trigger: Trigger<Pointer<Down>>, mut focused: ResMut<FocusedPane>) {
+    focused.set_if_neq(FocusedPane(Some(trigger.entity())));
+}
+
+/// Moves the focus between panes, sent by the editor when one of its key bindings is pressed.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MovePaneFocus {
+    /// Focuses the next pane in layout order, wrapping around.
+    Next,
+    /// Focuses the previous pane in layout order, wrapping around.
+    Previous,
+    /// Focuses the closest pane on the left.
+    Left,
+    /// Focuses the closest pane on the right.
+    Right,
+    /// Focuses the closest pane above.
+    Up,
+    /// Focuses the closest pane below.
+    Down,
+}
+
+/// Moves the focus between panes on [`MovePaneFocus`], the first pane is focused if none is.
+pub(crate) fn move_pane_focus(
+    mut events: EventReader<MovePaneFocus>,
+    mut focused: ResMut<FocusedPane>,
+    root_query: Query<Entity, With<RootPaneLayoutNode>>,
+    layout: PaneRects,
+) {
+    if events.is_empty() {
+        return;
+    }
+    let Ok(root) = root_query.get_single() else {
+        events.clear();
+        return;
+    };
+    let rects = layout.compute(root);
+    if rects.is_empty() {
+        events.clear();
+        return;
+    }
+
+    for event in events.read() {
+        let current = focused
+            .0
+            .and_then(|pane| rects.iter().position(|(entity, _)| *entity == pane));
+        let Some(index) = current else {
+            focused.set_if_neq(FocusedPane(Some(rects[0].0)));
+            continue;
+        };
+
+        let from = rects[index].1;
+        let next = match event {
+            MovePaneFocus::Next => Some(rects[(index + 1) % rects.len()].0),
+            MovePaneFocus::Previous => Some(rects[(index + rects.len() - 1) % rects.len()].0),
+            MovePaneFocus::Left => pane_in_direction(from, Divider::Horizontal, false, &rects),
+            MovePaneFocus::Right => pane_in_direction(from, Divider::Horizontal, true, &rects),
+            MovePaneFocus::Up => pane_in_direction(from, Divider::Vertical, false, &rects),
+            MovePaneFocus::Down => pane_in_direction(from, Divider::Vertical, true, &rects),
+        };
+        if let Some(next) = next {
+            focused.set_if_neq(FocusedPane(Some(next)));
+        }
+    }
+}
+
+/// Outlines the area of the focused pane, and forgets about panes that have been removed.
+///
+/// The outline is applied again when the [`Theme`] changes.
+pub(crate) fn apply_focus_outline(
+    mut commands: Commands,
+    mut focused: ResMut<FocusedPane>,
+    theme: Res<Theme>,
+    pane_query: Query<(), With<PaneRootNode>>,
+    area_query: Query<(Entity, &Parent, Has<Outline>), With<PaneAreaNode>>,
+) {
+    if let Some(pane) = focused.0 {
+        if !pane_query.contains(pane) {
+            focused.0 = None;
+        }
+    }
+
+    if !focused.is_changed() && !theme.is_changed() {
+        return;
+    }
+
+    for (area, parent, has_outline) in &area_query {
+        if focused.0 == Some(parent.get()) {
+            commands.entity(area).insert(Outline::new(
+                Val::Px(1.),
+                Val::Px(0.),
+                theme.colors.focus_ring,
+            ));
+        } else if has_outline {
+            commands.entity(area).remove::<Outline>();
+        }
+    }
+}
+
+/// Computes the rectangles of the panes within the unit square, from the sizes in the divider tree.
+#[derive(SystemParam)]
+pub(crate) struct PaneRects<'w, 's> {
+    children_query: Query<'w, 's, &'static Children>,
+    divider_query: Query<'w, 's, &'static Divider>,
+    size_query: Query<'w, 's, &'static Size>,
+    pane_query: Query<'w, 's, (), With<PaneRootNode>>,
+}
+
+impl PaneRects<'_, '_> {
+    /// Lists the panes below `root` in layout order, with their rectangles.
+    pub(crate) fn compute(&self, root: Entity) -> Vec<(Entity, Rect)> {
+        let mut rects = Vec::new();
+        self.visit(root, Rect::new(0., 0., 1., 1.), &mut rects);
+        rects
+    }
+
+    fn visit(&self, entity: Entity, rect: Rect, rects: &mut Vec<(Entity, Rect)>) {
+        if self.pane_query.contains(entity) {
+            rects.push((entity, rect));
+            return;
+        }
+        let Ok(children) = self.children_query.get(entity) else {
+            return;
+        };
+        let Ok(divider) = self.divider_query.get(entity) else {
+            for child in children {
+                self.visit(*child, rect, rects);
+            }
+            return;
+        };
+
+        let mut offset = 0.;
+        let size = |child| self.size_query.get(child).ok().map(|size| size.0);
+        for (child, size) in sized_children(children, size) {
+            let child_rect = match divider {
+                Divider::Horizontal => Rect::new(
+                    rect.min.x + offset * rect.width(),
+                    rect.min.y,
+                    rect.min.x + (offset + size) * rect.width(),
+                    rect.max.y,
+                ),
+                Divider::Vertical => Rect::new(
+                    rect.min.x,
+                    rect.min.y + offset * rect.height(),
+                    rect.max.x,
+                    rect.min.y + (offset + size) * rect.height(),
+                ),
+            };
+            offset += size;
+            self.visit(child, child_rect, rects);
+        }
+    }
+}
+
+/// Finds the closest pane next to `from` along `axis`, preferring panes that line up with the center of `from`.
+fn pane_in_direction(
+    from: Rect,
+    axis: Divider,
+    forward: bool,
+    rects: &[(Entity, Rect)],
+) -> Option<Entity> {
+    const EPSILON: f32 = 1e-4;
+    let cross = match axis {
+        Divider::Horizontal => Divider::Vertical,
+        Divider::Vertical => Divider::Horizontal,
+    };
+    let center = cross.main_axis(from.center());
+
+    rects
+        .iter()
+        .filter_map(|(entity, rect)| {
+            let gap = if forward {
+                axis.main_axis(rect.min) - axis.main_axis(from.max)
+            } else {
+                axis.main_axis(from.min) - axis.main_axis(rect.max)
+            };
+            if gap < -EPSILON {
+                return None;
+            }
+            // Distance between the center of `from` and the span of the candidate across the axis
+            let offset = (cross.main_axis(rect.min) - center)
+                .max(center - cross.main_axis(rect.max))
+                .max(0.);
+            Some((*entity, gap.max(0.), offset))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.2.total_cmp(&b.2)))
+        .map(|(entity, ..)| entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{pane, setup_app};
+
+    fn move_focus(app: &mut App, direction: MovePaneFocus) {
+        app.world_mut().send_event(direction);
+        app.update();
+    }
+
+    fn focused(app: &App) -> Option<Entity> {
+        app.world().resource::<FocusedPane>().get()
+    }
+
+    #[test]
+    fn cycle_focus() {
+        let mut app = setup_app();
+        let scene_tree = pane(&mut app, "Scene Tree");
+        let properties = pane(&mut app, "Properties");
+        let viewport = pane(&mut app, "Viewport 3D");
+
+        move_focus(&mut app, MovePaneFocus::Next);
+        assert_eq!(focused(&app), Some(scene_tree));
+        move_focus(&mut app, MovePaneFocus::Next);
+        assert_eq!(focused(&app), Some(properties));
+        move_focus(&mut app, MovePaneFocus::Next);
+        assert_eq!(focused(&app), Some(viewport));
+        move_focus(&mut app, MovePaneFocus::Next);
+        assert_eq!(focused(&app), Some(scene_tree));
+
+        move_focus(&mut app, MovePaneFocus::Previous);
+        assert_eq!(focused(&app), Some(viewport));
+    }
+
+    #[test]
+    fn directional_focus() {
+        let mut app = setup_app();
+        let scene_tree = pane(&mut app, "Scene Tree");
+        let properties = pane(&mut app, "Properties");
+        let viewport = pane(&mut app, "Viewport 3D");
+        app.insert_resource(FocusedPane(Some(scene_tree)));
+
+        move_focus(&mut app, MovePaneFocus::Down);
+        assert_eq!(focused(&app), Some(properties));
+        move_focus(&mut app, MovePaneFocus::Right);
+        assert_eq!(focused(&app), Some(viewport));
+        // Properties lines up with the center of the viewport
+        move_focus(&mut app, MovePaneFocus::Left);
+        assert_eq!(focused(&app), Some(properties));
+        move_focus(&mut app, MovePaneFocus::Up);
+        assert_eq!(focused(&app), Some(scene_tree));
+        // There is nothing above, the focus stays put
+        move_focus(&mut app, MovePaneFocus::Up);
+        assert_eq!(focused(&app), Some(scene_tree));
+    }
+
+    #[test]
+    fn forget_removed_pane() {
+        let mut app = setup_app();
+        let scene_tree = pane(&mut app, "Scene Tree");
+        app.insert_resource(FocusedPane(Some(scene_tree)));
+
+        app.world_mut().entity_mut(scene_tree).despawn_recursive();
+        app.update();
+
+        assert_eq!(focused(&app), None);
+    }
+}
@@ -0,0 +1,548 @@
+//! Programmatic manipulation of the pane layout.
+//!
+//! Everything the pointer handlers can do to the layout is available here as [`Commands`] extensions,
+//! so tools, scripts and tests can drive the layout too.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_editor_styles::Theme;
+
+use crate::{
+    collapse::{collapse_pane, expand_pane},
+    preset::apply_layout_preset,
+    ui::{insert_pane, spawn_divider, spawn_resize_handle},
+    Divider, PaneRootNode, RootPaneLayoutNode, Size,
+};
+
+/// Extension trait to manipulate the pane layout through [`Commands`].
+pub trait PaneLayoutCommandsExt {
+    /// Splits `pane` in two along `direction`, adding a new pane called `new_pane_name` after it.
+    ///
+    /// `ratio` is the fraction of the original space the existing pane keeps,
+    /// the new pane takes up the rest. Returns the entity of the new pane.
+    fn split_pane(
+        &mut self,
+        pane: Entity,
+        direction: Divider,
+        ratio: f32,
+        new_pane_name: impl Into<String>,
+    ) -> Entity;
+
+    /// Removes `pane` from the layout, giving its space to its neighbors.
+    ///
+    /// The last remaining pane can not be closed.
+    fn close_pane(&mut self, pane: Entity);
+
+    /// Removes `absorbed` from the layout, `pane` takes over its space.
+    ///
+    /// Both panes have to be next to each other within the same divider.
+    fn join_panes(&mut self, pane: Entity, absorbed: Entity);
+
+    /// Swaps the positions of two panes in the layout. Each pane takes over the size of the other.
+    fn swap_panes(&mut self, a: Entity, b: Entity);
+
+    /// Collapses `pane` down to its header.
+    fn collapse_pane(&mut self, pane: Entity);
+
+    /// Expands a collapsed pane back to its minimum size, taking the room from its siblings.
+    fn expand_pane(&mut self, pane: Entity);
+
+    /// Replaces the whole layout with the preset called `name` from the [`LayoutPresets`](crate::LayoutPresets).
+    fn apply_layout_preset(&mut self, name: impl Into<String>);
+
+    /// Resets the layout to the [default preset](crate::DEFAULT_LAYOUT_PRESET).
+    fn reset_layout(&mut self);
+
+    /// Sets the sizes of the children of `divider`, in order.
+    ///
+    /// The sizes are normalized, so `[1., 1.]` and `[0.5, 0.5]` are equivalent.
+    fn set_pane_sizes(&mut self, divider: Entity, sizes: impl Into<Vec<f32>>);
+}
+
+impl PaneLayoutCommandsExt for Commands<'_, '_> {
+    fn split_pane(
+        &mut self,
+        pane: Entity,
+        direction: Divider,
+        ratio: f32,
+        new_pane_name: impl Into<String>,
+    ) -> Entity {
+        let new_pane = self.spawn_empty().id();
+        let name = new_pane_name.into();
+        self.queue(move |world: &mut World| {
+            split_pane(world, pane, direction, ratio, new_pane, name);
+        });
+        new_pane
+    }
+
+    fn close_pane(&mut self, pane: Entity) {
+        self.queue(move |world: &mut World| close_pane(world, pane));
+    }
+
+    fn join_panes(&mut self, pane: Entity, absorbed: Entity) {
+        self.queue(move |world: &mut World| join_panes(world, pane, absorbed));
+    }
+
+    fn swap_panes(&mut self, a: Entity, b: Entity) {
+        self.queue(move |world: &mut World| swap_panes(world, a, b));
+    }
+
+    fn collapse_pane(&mut self, pane: Entity) {
+        self.queue(move |world: &mut World| collapse_pane(world, pane));
+    }
+
+    fn expand_pane(&mut self, pane: Entity) {
+        self.queue(move |world: &mut World| expand_pane(world, pane));
+    }
+
+    fn apply_layout_preset(&mut self, name: impl Into<String>) {
+        let name = name.into();
+        self.queue(move |world: &mut World| apply_layout_preset(world, &name));
+    }
+
+    fn reset_layout(&mut self) {
+        self.apply_layout_preset(crate::DEFAULT_LAYOUT_PRESET);
+    }
+
+    fn set_pane_sizes(&mut self, divider: Entity, sizes: impl Into<Vec<f32>>) {
+        let sizes = sizes.into();
+        self.queue(move |world: &mut World| set_pane_sizes(world, divider, &sizes));
+    }
+}
+
+/// Queries to find panes in the layout.
+#[derive(SystemParam)]
+pub struct Panes<'w, 's> {
+    panes: Query<'w, 's, (Entity, &'static PaneRootNode)>,
+    parent_query: Query<'w, 's, &'static Parent>,
+    divider_query: Query<'w, 's, (), With<Divider>>,
+}
+
+impl Panes<'_, '_> {
+    /// Iterates over all panes and their type names.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &str)> {
+        self.panes
+            .iter()
+            .map(|(entity, pane)| (entity, pane.name()))
+    }
+
+    /// Iterates over all panes of the given type.
+    pub fn iter_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = Entity> + 'a {
+        self.panes
+            .iter()
+            .filter(move |(_, pane)| pane.name() == name)
+            .map(|(entity, _)| entity)
+    }
+
+    /// Returns the first pane of the given type, if any.
+    pub fn first_by_name(&self, name: &str) -> Option<Entity> {
+        self.iter_by_name(name).next()
+    }
+
+    /// Returns the [`Divider`] entity `pane` is a child of, if any.
+    pub fn divider_of(&self, pane: Entity) -> Option<Entity> {
+        self.parent_query
+            .get(pane)
+            .ok()
+            .map(Parent::get)
+            .filter(|parent| self.divider_query.contains(*parent))
+    }
+}
+
+/// The areas among the `children` of a divider, with their [`Size`] as returned by `size`.
+///
+/// Resize handles are the only children of a divider without a size.
+pub(crate) fn sized_children<'a>(
+    children: &'a [Entity],
+    size: impl Fn(Entity) -> Option<f32> + 'a,
+) -> impl Iterator<Item = (Entity, f32)> + 'a {
+    children
+        .iter()
+        .filter_map(move |child| Some((*child, size(*child)?)))
+}
+
+/// Whether two panes can be joined, given the parent of each pane and its index among its siblings.
+///
+/// Only neighbors within the same divider can be joined, they only have a resize handle in between them.
+pub(crate) fn can_join(a: (Entity, usize), b: (Entity, usize)) -> bool {
+    a.0 == b.0 && a.1.abs_diff(b.1) == 2
+}
+
+/// Finds the parent of `pane` and its index among its siblings.
+fn pane_slot(world: &World, pane: Entity) -> Option<(Entity, usize)> {
+    world.get::<PaneRootNode>(pane)?;
+    let parent = world.get::<Parent>(pane)?.get();
+    let index = world
+        .get::<Children>(parent)?
+        .iter()
+        .position(|entity| *entity == pane)?;
+    Some((parent, index))
+}
+
+/// Splits `pane`, building the new pane on the already reserved `new_pane` entity.
+pub(crate) fn split_pane(
+    world: &mut World,
+    pane: Entity,
+    direction: Divider,
+    ratio: f32,
+    new_pane: Entity,
+    name: String,
+) {
+    let Some((parent, index)) = pane_slot(world, pane) else {
+        warn!("Can not split {pane}, it is not a pane in the layout");
+        world.despawn(new_pane);
+        return;
+    };
+    let ratio = ratio.clamp(0., 1.);
+
+    // Parent has a matching divider direction
+    let matching_direction = world.get::<Divider>(parent) == Some(&direction);
+
+    let size = world.get::<Size>(pane).unwrap().0;
+    let (pane_size, new_pane_size) = if matching_direction {
+        (size * ratio, size * (1. - ratio))
+    } else {
+        (ratio, 1. - ratio)
+    };
+
+    world.resource_scope(|world, theme: Mut<Theme>| {
+        let mut commands = world.commands();
+
+        // TODO The new pane should inherit the state of the existing pane
+        insert_pane(&mut commands, new_pane, &theme, new_pane_size, name);
+
+        let resize_handle = spawn_resize_handle(&mut commands, &theme, direction).id();
+
+        if matching_direction {
+            commands
+                .entity(parent)
+                .insert_children(index + 1, &[resize_handle, new_pane]);
+        } else {
+            let divider = spawn_divider(&mut commands, direction, size)
+                .add_children(&[pane, resize_handle, new_pane])
+                .id();
+            commands.entity(parent).insert_children(index, &[divider]);
+        }
+    });
+    world.flush();
+
+    world.get_mut::<Size>(pane).unwrap().0 = pane_size;
+}
+
+/// Removes `pane` and its resize handle, giving its size to its neighbors.
+pub(crate) fn close_pane(world: &mut World, pane: Entity) {
+    let Some((parent, index)) = pane_slot(world, pane) else {
+        warn!("Can not close {pane}, it is not a pane in the layout");
+        return;
+    };
+
+    // Prevent the removal of the last panel
+    if world.get::<RootPaneLayoutNode>(parent).is_some() {
+        return;
+    }
+
+    let siblings = world.get::<Children>(parent).unwrap().to_vec();
+    let size = world.get::<Size>(pane).unwrap().0;
+
+    let not_first_child = index != 0;
+
+    let a = not_first_child.then(|| siblings.get(index - 2)).flatten();
+    let b = siblings.get(index + 2);
+
+    match (a, b) {
+        // The divider is about to be cleaned up, the pane is already the last one in it
+        (None, None) => return,
+        (None, Some(e)) | (Some(e), None) => {
+            world.get_mut::<Size>(*e).unwrap().0 += size;
+        }
+        (Some(a), Some(b)) => {
+            world.get_mut::<Size>(*a).unwrap().0 += size / 2.;
+            world.get_mut::<Size>(*b).unwrap().0 += size / 2.;
+        }
+    }
+
+    // Despawn the resize handle next to this pane
+    let resize_handle_index = if not_first_child { index - 1 } else { 1 };
+    world
+        .entity_mut(siblings[resize_handle_index])
+        .despawn_recursive();
+    // Despawn this pane
+    world.entity_mut(pane).despawn_recursive();
+}
+
+/// Removes `absorbed` and the resize handle next to it, giving all of its size to `pane`.
+pub(crate) fn join_panes(world: &mut World, pane: Entity, absorbed: Entity) {
+    let (Some((parent, index)), Some((absorbed_parent, absorbed_index))) =
+        (pane_slot(world, pane), pane_slot(world, absorbed))
+    else {
+        warn!("Can not join {pane} and {absorbed}, both need to be panes in the layout");
+        return;
+    };
+
+    if !can_join((parent, index), (absorbed_parent, absorbed_index)) {
+        warn!("Can not join {pane} and {absorbed}, they are not next to each other");
+        return;
+    }
+
+    let resize_handle = world.get::<Children>(parent).unwrap()[(index + absorbed_index) / 2];
+    let size = world.get::<Size>(absorbed).unwrap().0;
+    world.get_mut::<Size>(pane).unwrap().0 += size;
+
+    world.entity_mut(resize_handle).despawn_recursive();
+    world.entity_mut(absorbed).despawn_recursive();
+}
+
+/// Swaps the positions and sizes of two panes.
+pub(crate) fn swap_panes(world: &mut World, a: Entity, b: Entity) {
+    if a == b {
+        return;
+    }
+    let (Some((parent_a, index_a)), Some((parent_b, index_b))) =
+        (pane_slot(world, a), pane_slot(world, b))
+    else {
+        warn!("Can not swap {a} and {b}, both need to be panes in the layout");
+        return;
+    };
+
+    // `insert_children` removes the child from its previous position first,
+    // so inserting one after the other leaves both panes in each other's place.
+    world.entity_mut(parent_a).insert_children(index_a, &[b]);
+    world.entity_mut(parent_b).insert_children(index_b, &[a]);
+
+    let size_a = world.get::<Size>(a).unwrap().0;
+    let size_b = world.get::<Size>(b).unwrap().0;
+    world.get_mut::<Size>(a).unwrap().0 = size_b;
+    world.get_mut::<Size>(b).unwrap().0 = size_a;
+}
+
+/// Sets the normalized sizes of the panes and dividers within `divider`.
+pub(crate) fn set_pane_sizes(world: &mut World, divider: Entity, sizes: &[f32]) {
+    if world.get::<Divider>(divider).is_none() {
+        warn!("Can not set the pane sizes of {divider}, it is not a divider");
+        return;
+    }
+
+    let children: Vec<Entity> = world
+        .get::<Children>(divider)
+        .map(|children| {
+            sized_children(children, |child| {
+                world.get::<Size>(child).map(|size| size.0)
+            })
+            .map(|(child, _)| child)
+            .collect()
+        })
+        .unwrap_or_default();
+
+    if children.len() != sizes.len() {
+        warn!(
+            "Expected {} sizes for divider {divider}, got {}",
+            children.len(),
+            sizes.len()
+        );
+        return;
+    }
+
+    let total: f32 = sizes.iter().sum();
+    if total <= 0. || sizes.iter().any(|size| *size < 0.) {
+        warn!("Pane sizes must be positive, got {sizes:?}");
+        return;
+    }
+
+    for (child, size) in children.into_iter().zip(sizes) {
+        world.get_mut::<Size>(child).unwrap().0 = size / total;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{apply, pane, setup_app};
+
+    fn pane_count(app: &mut App) -> usize {
+        app.world_mut()
+            .query::<&PaneRootNode>()
+            .iter(app.world())
+            .count()
+    }
+
+    fn size(app: &App, entity: Entity) -> f32 {
+        app.world().get::<Size>(entity).unwrap().0
+    }
+
+    fn assert_size(app: &App, entity: Entity, expected: f32) {
+        let size = size(app, entity);
+        assert!(
+            (size - expected).abs() < 1e-5,
+            "expected size {expected}, got {size}"
+        );
+    }
+
+    fn exists(app: &App, entity: Entity) -> bool {
+        app.world().entities().contains(entity)
+    }
+
+    fn parent(app: &App, entity: Entity) -> Entity {
+        app.world().get::<Parent>(entity).unwrap().get()
+    }
+
+    #[test]
+    fn split_along_parent_direction() {
+        let mut app = setup_app();
+        let scene_tree = pane(&mut app, "Scene Tree");
+        let divider = parent(&app, scene_tree);
+
+        let mut new_pane = Entity::PLACEHOLDER;
+        apply(&mut app, |commands| {
+            new_pane = commands.split_pane(scene_tree, Divider::Vertical, 0.25, "Properties");
+        });
+
+        assert_eq!(pane_count(&mut app), 4);
+        assert_eq!(parent(&app, new_pane), divider);
+        assert_size(&app, scene_tree, 0.1);
+        assert_size(&app, new_pane, 0.3);
+
+        // The new pane and its resize handle are placed right after the split pane
+        let children = app.world().get::<Children>(divider).unwrap();
+        assert_eq!(children[0], scene_tree);
+        assert_eq!(children[2], new_pane);
+    }
+
+    #[test]
+    fn split_across_parent_direction() {
+        let mut app = setup_app();
+        let viewport = pane(&mut app, "Viewport 3D");
+        let old_parent = parent(&app, viewport);
+
+        let mut new_pane = Entity::PLACEHOLDER;
+        apply(&mut app, |commands| {
+            new_pane = commands.split_pane(viewport, Divider::Vertical, 0.7, "Scene Tree");
+        });
+
+        let new_divider = parent(&app, viewport);
+        assert_ne!(new_divider, old_parent);
+        assert_eq!(parent(&app, new_divider), old_parent);
+        assert_eq!(parent(&app, new_pane), new_divider);
+        assert_eq!(
+            app.world().get::<Divider>(new_divider),
+            Some(&Divider::Vertical)
+        );
+        assert_size(&app, new_divider, 0.8);
+        assert_size(&app, viewport, 0.7);
+        assert_size(&app, new_pane, 0.3);
+    }
+
+    #[test]
+    fn split_invalid_entity() {
+        let mut app = setup_app();
+        let not_a_pane = app.world_mut().spawn_empty().id();
+
+        let mut new_pane = Entity::PLACEHOLDER;
+        apply(&mut app, |commands| {
+            new_pane = commands.split_pane(not_a_pane, Divider::Vertical, 0.5, "Properties");
+        });
+
+        assert_eq!(pane_count(&mut app), 3);
+        assert!(!exists(&app, new_pane));
+    }
+
+    #[test]
+    fn close() {
+        let mut app = setup_app();
+        let scene_tree = pane(&mut app, "Scene Tree");
+        let properties = pane(&mut app, "Properties");
+        let viewport = pane(&mut app, "Viewport 3D");
+        let sub_divider = parent(&app, scene_tree);
+        let root_divider = parent(&app, viewport);
+
+        apply(&mut app, |commands| commands.close_pane(scene_tree));
+
+        assert_eq!(pane_count(&mut app), 2);
+        // The divider that is left with a single child is replaced by it
+        assert!(!exists(&app, sub_divider));
+        assert_eq!(parent(&app, properties), root_divider);
+        assert_size(&app, properties, 0.2);
+    }
+
+    #[test]
+    fn close_last_pane() {
+        let mut app = setup_app();
+        let scene_tree = pane(&mut app, "Scene Tree");
+        let properties = pane(&mut app, "Properties");
+        let viewport = pane(&mut app, "Viewport 3D");
+
+        apply(&mut app, |commands| commands.close_pane(scene_tree));
+        apply(&mut app, |commands| commands.close_pane(properties));
+        assert_eq!(pane_count(&mut app), 1);
+        assert_size(&app, viewport, 1.);
+
+        apply(&mut app, |commands| commands.close_pane(viewport));
+        assert_eq!(pane_count(&mut app), 1);
+    }
+
+    #[test]
+    fn join() {
+        let mut app = setup_app();
+        let scene_tree = pane(&mut app, "Scene Tree");
+        let properties = pane(&mut app, "Properties");
+        let viewport = pane(&mut app, "Viewport 3D");
+        let root_divider = parent(&app, viewport);
+
+        // Only neighbors within the same divider can be joined
+        apply(&mut app, |commands| {
+            commands.join_panes(viewport, scene_tree);
+        });
+        assert_eq!(pane_count(&mut app), 3);
+
+        apply(&mut app, |commands| {
+            commands.join_panes(properties, scene_tree);
+        });
+        assert_eq!(pane_count(&mut app), 2);
+        assert!(!exists(&app, scene_tree));
+        assert_eq!(parent(&app, properties), root_divider);
+        assert_size(&app, properties, 0.2);
+    }
+
+    #[test]
+    fn swap() {
+        let mut app = setup_app();
+        let scene_tree = pane(&mut app, "Scene Tree");
+        let viewport = pane(&mut app, "Viewport 3D");
+        let sub_divider = parent(&app, scene_tree);
+        let root_divider = parent(&app, viewport);
+
+        apply(&mut app, |commands| {
+            commands.swap_panes(scene_tree, viewport);
+        });
+
+        assert_eq!(parent(&app, scene_tree), root_divider);
+        assert_eq!(parent(&app, viewport), sub_divider);
+        assert_eq!(
+            app.world().get::<Children>(sub_divider).unwrap()[0],
+            viewport
+        );
+        assert_eq!(
+            app.world().get::<Children>(root_divider).unwrap()[2],
+            scene_tree
+        );
+        assert_size(&app, scene_tree, 0.8);
+        assert_size(&app, viewport, 0.4);
+    }
+
+    #[test]
+    fn set_sizes() {
+        let mut app = setup_app();
+        let scene_tree = pane(&mut app, "Scene Tree");
+        let properties = pane(&mut app, "Properties");
+        let divider = parent(&app, scene_tree);
+
+        apply(&mut app, |commands| {
+            commands.set_pane_sizes(divider, [1., 3.]);
+        });
+
+        assert_size(&app, scene_tree, 0.25);
+        assert_size(&app, properties, 0.75);
+
+        // Mismatched sizes are ignored
+        apply(&mut app, |commands| commands.set_pane_sizes(divider, [1.]));
+        assert_size(&app, scene_tree, 0.25);
+    }
+}
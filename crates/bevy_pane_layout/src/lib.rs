@@ -1,8 +1,25 @@
 //! Resizable, divider-able panes for Bevy.
 
+mod collapse;
+mod corner;
+mod focus;
 mod handlers;
+mod layout;
+mod preset;
+mod resize;
+mod scroll;
+#[cfg(test)]
+mod testing;
 mod ui;
 
+pub use collapse::PaneCollapsed;
+pub use focus::{FocusedPane, MovePaneFocus};
+pub use layout::{PaneLayoutCommandsExt, Panes};
+pub use preset::{
+    ApplyLayoutPreset, LayoutPresets, PaneLayout, ResetLayout, DEFAULT_LAYOUT_PRESET,
+};
+pub use scroll::PaneScroll;
+
 /// The Bevy Pane Layout system.
 /// The intent of this system is to provide a way to create resizable, split-able panes in Bevy.
 /// Mimicking the behavior of of Blender's layout system.
@@ -13,13 +30,23 @@ mod ui;
 /// - All panes must fit within their bounds, no overflow is allowed.
 /// - Panes can not have power over the layout system, their dimensions are controlled by the layout system and should not be modified by anything else.
 /// - All panes must have a header, a content area, however a footer is optional.
-/// - Panes cannot have min/max sizes, they must be able to be resized to any size.
-///   - If a pane can not be sensibly resized, it can overflow under the other panes.
+///   - The header has [`PaneHeaderSlot`]s for pane specific widgets, the footer is opted into with [`Pane::with_footer`].
+///   - Content that does not fit scrolls, the scroll offset is kept in [`PaneScroll`].
+/// - Panes can have a preferred minimum size, but they must be able to be resized to any size.
+///   - If a pane stacked in a vertical divider does not get its minimum height, it collapses down to its header until it is expanded again.
 /// - Panes must not interfere with each other, only temporary/absolute positioned elements are allowed to overlap panes.
-use bevy::prelude::*;
+use bevy::{input::mouse::MouseWheel, prelude::*};
 use bevy_editor_styles::Theme;
 
-use crate::ui::{spawn_divider, spawn_pane, spawn_resize_handle};
+use crate::{
+    collapse::{apply_collapsed_display, update_collapsed_panes, PaneMinSize},
+    corner::CornerDragState,
+    focus::{apply_focus_outline, move_pane_focus},
+    preset::{apply_requested_presets, spawn_layout},
+    resize::restyle_resize_grips,
+    scroll::{apply_pane_scroll, restyle_scrollbars, scroll_panes_with_wheel},
+    ui::{restyle_panes, spawn_footer},
+};
 
 /// The Bevy Pane Layout Plugin.
 pub struct PaneLayoutPlugin;
@@ -29,22 +56,47 @@ impl Plugin for PaneLayoutPlugin {
         let mut pane_registry = app.world_mut().get_resource_or_init::<PaneRegistry>();
 
         // TODO Move these registrations to their respective crates.
-        pane_registry.register("Properties", |mut _commands, _pane_root| {
-            // Todo
-        });
-
-        pane_registry.register("Scene Tree", |mut _commands, _pane_root| {
-            // Todo
-        });
+        pane_registry
+            .register("Properties", |mut _commands, _pane| {
+                // Todo
+            })
+            .min_size(Vec2::new(200., 150.));
+
+        pane_registry
+            .register("Scene Tree", |mut _commands, _pane| {
+                // Todo
+            })
+            .min_size(Vec2::new(150., 150.));
 
         app.init_resource::<DragState>()
+            .init_resource::<CornerDragState>()
+            .init_resource::<FocusedPane>()
+            .init_resource::<LayoutPresets>()
             .init_resource::<PaneRegistry>()
+            .add_event::<ApplyLayoutPreset>()
+            .add_event::<ResetLayout>()
+            .add_event::<MovePaneFocus>()
             .add_systems(Startup, setup.in_set(PaneLayoutSet))
             .add_systems(
                 Update,
                 (
-                    (cleanup_divider_single_child, apply_size).chain(),
-                    on_pane_creation,
+                    (
+                        cleanup_divider_single_child,
+                        update_collapsed_panes,
+                        apply_size,
+                    )
+                        .chain(),
+                    // The panes of a preset are set up in the same frame
+                    (apply_requested_presets, on_pane_creation).chain(),
+                    apply_collapsed_display,
+                    (move_pane_focus, apply_focus_outline).chain(),
+                    (
+                        scroll_panes_with_wheel.run_if(resource_exists::<Events<MouseWheel>>),
+                        apply_pane_scroll,
+                    )
+                        .chain(),
+                    (restyle_panes, restyle_scrollbars, restyle_resize_grips)
+                        .run_if(resource_changed::<Theme>),
                 )
                     .in_set(PaneLayoutSet),
             );
@@ -84,21 +136,35 @@ struct DragState {
     min: f32,
     max: f32,
     parent_node_size: f32,
+    /// The sizes on either side of the handle when the drag started.
+    start_sizes: (f32, f32),
+    /// Where the handle was within its divider when the drag started, as a fraction.
+    start_position: f32,
+    /// The tooltip showing the sizes while dragging.
+    tooltip: Option<Entity>,
 }
 
 fn on_pane_creation(
-    mut query: Query<(Entity, &PaneRootNode), Added<PaneRootNode>>,
+    mut query: Query<(Entity, &PaneRootNode, &PaneStructure), Added<PaneRootNode>>,
     mut pane_registry: ResMut<PaneRegistry>,
+    theme: Res<Theme>,
     mut commands: Commands,
 ) {
-    for (entity, pane_root) in &mut query {
+    for (entity, pane_root, structure) in &mut query {
         let pane = pane_registry
             .panes
             .iter_mut()
             .find(|pane| pane.name == pane_root.name);
 
         if let Some(pane) = pane {
-            (pane.creation_callback)(commands.reborrow(), entity);
+            let mut structure = *structure;
+            if pane.footer {
+                structure.footer = Some(spawn_footer(&mut commands, &theme, structure.area).id());
+            }
+            commands
+                .entity(entity)
+                .insert((PaneMinSize(pane.min_size), structure));
+            (pane.creation_callback)(commands.reborrow(), structure);
         } else {
             warn!(
                 "No pane found in the registry with name: '{}'",
@@ -123,29 +189,52 @@ impl PaneRegistry {
     pub fn register(
         &mut self,
         name: impl Into<String>,
-        creation_callback: impl FnMut(Commands, Entity) + Send + Sync + 'static,
-    ) {
+        creation_callback: impl FnMut(Commands, PaneStructure) + Send + Sync + 'static,
+    ) -> &mut Pane {
         self.panes.push(Pane {
             name: name.into(),
             creation_callback: Box::new(creation_callback),
+            min_size: Vec2::ZERO,
+            footer: false,
         });
+        self.panes.last_mut().unwrap()
     }
 }
 
-struct Pane {
+/// A pane type in the [`PaneRegistry`].
+pub struct Pane {
     name: String,
-    creation_callback: Box<dyn FnMut(Commands, Entity) + Send + Sync>,
+    creation_callback: Box<dyn FnMut(Commands, PaneStructure) + Send + Sync>,
+    min_size: Vec2,
+    footer: bool,
+}
+
+impl Pane {
+    /// Sets the preferred minimum size of this pane type in logical pixels.
+    ///
+    /// Panes that don't get this much room collapse down to their header.
+    pub fn min_size(&mut self, min_size: Vec2) -> &mut Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Gives panes of this type a [`PaneFooterNode`] below their content, for status information.
+    pub fn with_footer(&mut self) -> &mut Self {
+        self.footer = true;
+        self
+    }
 }
 
 // TODO There is no way to save or load layouts at this moment.
-// The setup system currently just creates a default layout at startup.
+// The setup system currently just creates the default preset at startup.
 fn setup(
     mut commands: Commands,
     theme: Res<Theme>,
+    presets: Res<LayoutPresets>,
     panes_root: Single<Entity, With<RootPaneLayoutNode>>,
 ) {
     commands.entity(*panes_root).insert(NodeBundle {
-        background_color: theme.background_color,
+        background_color: BackgroundColor(theme.colors.surface.background),
         style: Style {
             padding: UiRect::all(Val::Px(1.)),
             height: Val::Percent(100.),
@@ -155,22 +244,12 @@ fn setup(
         ..default()
     });
 
-    let divider = spawn_divider(&mut commands, Divider::Horizontal, 1.)
-        .set_parent(*panes_root)
-        .id();
-
-    // spawn_pane(&mut commands, &theme, 0.2, "Scene Tree").set_parent(divider);
-
-    let sub_divider = spawn_divider(&mut commands, Divider::Vertical, 0.2)
-        .set_parent(divider)
-        .id();
-
-    spawn_pane(&mut commands, &theme, 0.4, "Scene Tree").set_parent(sub_divider);
-    spawn_resize_handle(&mut commands, Divider::Vertical).set_parent(sub_divider);
-    spawn_pane(&mut commands, &theme, 0.6, "Properties").set_parent(sub_divider);
-
-    spawn_resize_handle(&mut commands, Divider::Horizontal).set_parent(divider);
-    spawn_pane(&mut commands, &theme, 0.8, "Viewport 3D").set_parent(divider);
+    let Some(layout) = presets.get(DEFAULT_LAYOUT_PRESET) else {
+        warn!("No layout preset found with name: '{DEFAULT_LAYOUT_PRESET}'");
+        return;
+    };
+    let layout = spawn_layout(&mut commands, &theme, layout, 1.);
+    commands.entity(*panes_root).add_child(layout);
 }
 
 /// Removes a divider from the hierarchy when it has only one child left, replacing itself with that child.
@@ -205,12 +284,24 @@ fn cleanup_divider_single_child(
 }
 
 /// A node that divides an area into multiple areas along an axis.
-#[derive(Component, Clone, Copy, PartialEq, Eq)]
-enum Divider {
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Divider {
+    /// Areas are placed next to each other, left to right.
     Horizontal,
+    /// Areas are stacked on top of each other, top to bottom.
     Vertical,
 }
 
+impl Divider {
+    /// Picks the component of `size` along the axis this divider splits.
+    pub(crate) fn main_axis(self, size: Vec2) -> f32 {
+        match self {
+            Divider::Horizontal => size.x,
+            Divider::Vertical => size.y,
+        }
+    }
+}
+
 #[derive(Component)]
 struct ResizeHandle;
 
@@ -224,10 +315,17 @@ pub struct RootPaneLayoutNode;
 
 /// Root node for each pane, holds all event nodes for layout and the basic structure for all Panes.
 #[derive(Component)]
-struct PaneRootNode {
+pub struct PaneRootNode {
     name: String,
 }
 
+impl PaneRootNode {
+    /// The name of the pane type, as registered in the [`PaneRegistry`].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
 /// Node to denote the area of the Pane.
 #[derive(Component)]
 pub struct PaneAreaNode;
@@ -236,6 +334,185 @@ pub struct PaneAreaNode;
 #[derive(Component)]
 pub struct PaneHeaderNode;
 
+/// Regions of the header, after the title, to add pane specific widgets into.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaneHeaderSlot {
+    /// Widgets placed right after the title.
+    Left,
+    /// Widgets centered in the remaining space of the header.
+    Center,
+    /// Widgets aligned to the end of the header.
+    Right,
+}
+
 /// Node to denote the content space of the Pane.
 #[derive(Component)]
 pub struct PaneContentNode;
+
+/// Node to add status information into the bottom of a Pane, only present if the pane type asked for it with [`Pane::with_footer`].
+#[derive(Component)]
+pub struct PaneFooterNode;
+
+/// The entities making up a pane, stored on the [`PaneRootNode`] and handed to the creation callback of the pane type.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PaneStructure {
+    /// The [`PaneRootNode`].
+    pub root: Entity,
+    /// The [`PaneAreaNode`].
+    pub area: Entity,
+    /// The [`PaneHeaderNode`].
+    pub header: Entity,
+    /// The [`PaneHeaderSlot::Left`] node of the header.
+    pub header_left: Entity,
+    /// The [`PaneHeaderSlot::Center`] node of the header.
+    pub header_center: Entity,
+    /// The [`PaneHeaderSlot::Right`] node of the header.
+    pub header_right: Entity,
+    /// The [`PaneContentNode`].
+    pub content: Entity,
+    /// The [`PaneFooterNode`], if the pane type has one.
+    pub footer: Option<Entity>,
+}
+
+impl PaneStructure {
+    /// Returns the node of the given header slot.
+    pub fn header_slot(&self, slot: PaneHeaderSlot) -> Entity {
+        match slot {
+            PaneHeaderSlot::Left => self.header_left,
+            PaneHeaderSlot::Center => self.header_center,
+            PaneHeaderSlot::Right => self.header_right,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_editor_styles::StylesPlugin;
+
+    use crate::testing::layout_app;
+
+    #[derive(Component)]
+    struct Status;
+
+    #[test]
+    fn footer_and_header_slots() {
+        let mut app = layout_app();
+        app.world_mut()
+            .resource_mut::<PaneRegistry>()
+            .register("Console", |mut commands, pane| {
+                commands.entity(pane.footer.unwrap()).with_child(Status);
+                commands
+                    .entity(pane.header_slot(PaneHeaderSlot::Right))
+                    .with_child(Status);
+            })
+            .with_footer();
+        app.world_mut().resource_mut::<LayoutPresets>().register(
+            DEFAULT_LAYOUT_PRESET,
+            PaneLayout::vertical([
+                (0.5, PaneLayout::pane("Console")),
+                (0.5, PaneLayout::pane("Properties")),
+            ]),
+        );
+        app.world_mut().spawn(RootPaneLayoutNode);
+        app.update();
+        app.update();
+
+        let structures: Vec<(String, PaneStructure)> = app
+            .world_mut()
+            .query::<(&PaneRootNode, &PaneStructure)>()
+            .iter(app.world())
+            .map(|(pane, structure)| (pane.name().to_string(), *structure))
+            .collect();
+        assert_eq!(structures.len(), 2);
+
+        for (name, structure) in structures {
+            let world = app.world();
+            assert_eq!(
+                world.get::<Parent>(structure.header_right).unwrap().get(),
+                structure.header
+            );
+            if name == "Console" {
+                let footer = structure.footer.unwrap();
+                assert!(world.get::<PaneFooterNode>(footer).is_some());
+                assert_eq!(world.get::<Parent>(footer).unwrap().get(), structure.area);
+                assert!(world
+                    .get::<Status>(world.get::<Children>(footer).unwrap()[0])
+                    .is_some());
+                assert!(world.get::<Children>(structure.header_right).is_some());
+            } else {
+                assert!(structure.footer.is_none());
+                assert!(world.get::<Children>(structure.header_right).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn restyle_on_theme_change() {
+        let mut app = layout_app();
+        app.add_plugins(StylesPlugin);
+        app.world_mut().spawn(RootPaneLayoutNode);
+        app.update();
+        app.update();
+
+        let header_color = Color::oklch(0.9, 0., 0.);
+        let text_color = Color::oklch(0.1, 0., 0.);
+        let mut theme = app.world_mut().resource_mut::<Theme>();
+        theme.colors.surface.header = header_color;
+        theme.colors.text.primary = text_color;
+        theme.sizes.pane_header = 32.;
+        theme.typography.label.size = 18.;
+        theme.sizes.grip_length = 40.;
+        theme.sizes.pane_padding = 3.;
+        theme.sizes.resize_handle = 9.;
+        app.update();
+
+        let mut header_query = app
+            .world_mut()
+            .query_filtered::<(&BackgroundColor, &Style), With<PaneHeaderNode>>();
+        let headers: Vec<(&BackgroundColor, &Style)> = header_query.iter(app.world()).collect();
+        assert!(!headers.is_empty());
+        for (background_color, style) in headers {
+            assert_eq!(background_color.0, header_color);
+            assert_eq!(style.height, Val::Px(32.));
+        }
+
+        // The title is the first child of the header
+        let mut title_query = app
+            .world_mut()
+            .query_filtered::<&Children, With<PaneHeaderNode>>();
+        for children in title_query.iter(app.world()) {
+            let title = app.world().get::<TextColor>(children[0]).unwrap();
+            assert_eq!(title.0, text_color);
+            let title = app.world().get::<TextFont>(children[0]).unwrap();
+            assert_eq!(title.font_size, 18.);
+        }
+
+        // The default layout has a handle in each of its dividers
+        let mut grip_query = app
+            .world_mut()
+            .query_filtered::<&Children, With<ResizeHandle>>();
+        let grips: Vec<Entity> = grip_query.iter(app.world()).flatten().copied().collect();
+        assert_eq!(grips.len(), 2);
+        for grip in grips {
+            let style = app.world().get::<Style>(grip).unwrap();
+            assert!(style.width == Val::Px(40.) || style.height == Val::Px(40.));
+        }
+
+        let mut handle_query = app
+            .world_mut()
+            .query_filtered::<(&Style, &Parent), With<ResizeHandle>>();
+        for (style, root) in handle_query.iter(app.world()) {
+            assert!(style.width == Val::Px(9.) || style.height == Val::Px(9.));
+            let root = app.world().get::<Style>(root.get()).unwrap();
+            assert!(root.margin.left == Val::Px(-4.5) || root.margin.top == Val::Px(-4.5));
+        }
+
+        let mut pane_query = app
+            .world_mut()
+            .query_filtered::<&Style, With<PaneRootNode>>();
+        for style in pane_query.iter(app.world()) {
+            assert_eq!(style.padding, UiRect::all(Val::Px(3.)));
+        }
+    }
+}
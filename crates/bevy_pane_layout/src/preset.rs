@@ -0,0 +1,272 @@
+//! Named layout presets, described declaratively and spawned on demand.
+
+use bevy::prelude::*;
+use bevy_editor_styles::Theme;
+
+use crate::{
+    ui::{spawn_divider, spawn_pane, spawn_resize_handle},
+    Divider, PaneLayoutCommandsExt, RootPaneLayoutNode,
+};
+
+/// The name of the preset the editor starts with, and resets to.
+pub const DEFAULT_LAYOUT_PRESET: &str = "Default";
+
+/// Send this event to replace the layout with the preset of the given name, e.g. from a menu.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct ApplyLayoutPreset(pub String);
+
+/// Send this event to reset the layout to the [default preset](DEFAULT_LAYOUT_PRESET).
+#[derive(Event, Clone, Copy, Debug, Default)]
+pub struct ResetLayout;
+
+/// A declarative description of a pane layout.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaneLayout {
+    /// A pane of the type with the given name, as registered in the [`PaneRegistry`](crate::PaneRegistry).
+    Pane(String),
+    /// An area divided along an axis.
+    Divider {
+        /// The axis along which the area is divided.
+        direction: Divider,
+        /// The children with their share of the space, the shares are normalized.
+        children: Vec<(f32, PaneLayout)>,
+    },
+}
+
+impl PaneLayout {
+    /// A pane of the type with the given name.
+    pub fn pane(name: impl Into<String>) -> Self {
+        Self::Pane(name.into())
+    }
+
+    /// Areas next to each other, left to right.
+    pub fn horizontal(children: impl IntoIterator<Item = (f32, PaneLayout)>) -> Self {
+        Self::Divider {
+            direction: Divider::Horizontal,
+            children: children.into_iter().collect(),
+        }
+    }
+
+    /// Areas on top of each other, top to bottom.
+    pub fn vertical(children: impl IntoIterator<Item = (f32, PaneLayout)>) -> Self {
+        Self::Divider {
+            direction: Divider::Vertical,
+            children: children.into_iter().collect(),
+        }
+    }
+}
+
+/// A registry of named layout presets.
+///
+/// Pane crates can contribute their own presets, registering a preset with an existing name replaces it.
+#[derive(Resource)]
+pub struct LayoutPresets {
+    presets: Vec<(String, PaneLayout)>,
+}
+
+impl Default for LayoutPresets {
+    fn default() -> Self {
+        let mut presets = Self {
+            presets: Vec::new(),
+        };
+
+        presets.register(
+            DEFAULT_LAYOUT_PRESET,
+            PaneLayout::horizontal([
+                (
+                    0.2,
+                    PaneLayout::vertical([
+                        (0.4, PaneLayout::pane("Scene Tree")),
+                        (0.6, PaneLayout::pane("Properties")),
+                    ]),
+                ),
+                (0.8, PaneLayout::pane("Viewport 3D")),
+            ]),
+        );
+
+        presets.register(
+            "Debug Inspector",
+            PaneLayout::horizontal([
+                (0.6, PaneLayout::pane("Viewport 3D")),
+                (
+                    0.4,
+                    PaneLayout::vertical([
+                        (0.5, PaneLayout::pane("Scene Tree")),
+                        (0.5, PaneLayout::pane("Properties")),
+                    ]),
+                ),
+            ]),
+        );
+
+        presets
+    }
+}
+
+impl LayoutPresets {
+    /// Register a new preset, replacing any preset with the same name.
+    pub fn register(&mut self, name: impl Into<String>, layout: PaneLayout) {
+        let name = name.into();
+        if let Some((_, existing)) = self.presets.iter_mut().find(|(n, _)| *n == name) {
+            *existing = layout;
+        } else {
+            self.presets.push((name, layout));
+        }
+    }
+
+    /// Returns the preset with the given name, if any.
+    pub fn get(&self, name: &str) -> Option<&PaneLayout> {
+        self.presets
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, layout)| layout)
+    }
+
+    /// Iterates over the presets in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PaneLayout)> {
+        self.presets
+            .iter()
+            .map(|(name, layout)| (name.as_str(), layout))
+    }
+}
+
+/// Spawns the entities described by `layout`, returning the top-most one.
+pub(crate) fn spawn_layout(
+    commands: &mut Commands,
+    theme: &Theme,
+    layout: &PaneLayout,
+    size: f32,
+) -> Entity {
+    match layout {
+        PaneLayout::Pane(name) => spawn_pane(commands, theme, size, name.clone()).id(),
+        PaneLayout::Divider {
+            direction,
+            children,
+        } => {
+            let divider = spawn_divider(commands, *direction, size).id();
+            let total: f32 = children.iter().map(|(size, _)| size).sum();
+
+            for (index, (child_size, child)) in children.iter().enumerate() {
+                if index != 0 {
+                    spawn_resize_handle(commands, theme, *direction).set_parent(divider);
+                }
+                let child = spawn_layout(commands, theme, child, child_size / total);
+                commands.entity(child).set_parent(divider);
+            }
+
+            divider
+        }
+    }
+}
+
+/// Applies the presets requested with [`ApplyLayoutPreset`] and [`ResetLayout`].
+pub(crate) fn apply_requested_presets(
+    mut commands: Commands,
+    mut preset_events: EventReader<ApplyLayoutPreset>,
+    mut reset_events: EventReader<ResetLayout>,
+) {
+    for ApplyLayoutPreset(name) in preset_events.read() {
+        commands.apply_layout_preset(name.clone());
+    }
+    if reset_events.read().count() > 0 {
+        commands.reset_layout();
+    }
+}
+
+/// Replaces the current layout with the preset called `name`.
+pub(crate) fn apply_layout_preset(world: &mut World, name: &str) {
+    let Some(layout) = world.resource::<LayoutPresets>().get(name).cloned() else {
+        warn!("No layout preset found with name: '{name}'");
+        return;
+    };
+
+    let mut root_query = world.query_filtered::<Entity, With<RootPaneLayoutNode>>();
+    let Ok(root) = root_query.get_single(world) else {
+        warn!("Can not apply layout preset '{name}' without a single pane layout root");
+        return;
+    };
+
+    world.entity_mut(root).despawn_descendants();
+
+    world.resource_scope(|world, theme: Mut<Theme>| {
+        let mut commands = world.commands();
+        let layout = spawn_layout(&mut commands, &theme, &layout, 1.);
+        commands.entity(root).add_child(layout);
+    });
+    world.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{apply, pane, setup_app},
+        PaneRootNode, Size,
+    };
+
+    fn pane_names(app: &mut App) -> Vec<String> {
+        let mut names: Vec<String> = app
+            .world_mut()
+            .query::<&PaneRootNode>()
+            .iter(app.world())
+            .map(|pane| pane.name().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn viewport_size(app: &mut App) -> f32 {
+        let viewport = pane(app, "Viewport 3D");
+        app.world().get::<Size>(viewport).unwrap().0
+    }
+
+    #[test]
+    fn apply_and_reset() {
+        let mut app = setup_app();
+        assert_eq!(
+            pane_names(&mut app),
+            ["Properties", "Scene Tree", "Viewport 3D"]
+        );
+
+        apply(&mut app, |commands| {
+            commands.apply_layout_preset("Debug Inspector");
+        });
+        assert_eq!(
+            pane_names(&mut app),
+            ["Properties", "Scene Tree", "Viewport 3D"]
+        );
+        assert_eq!(viewport_size(&mut app), 0.6);
+
+        apply(&mut app, |commands| commands.reset_layout());
+        assert_eq!(viewport_size(&mut app), 0.8);
+    }
+
+    #[test]
+    fn preset_events() {
+        let mut app = setup_app();
+        app.world_mut()
+            .send_event(ApplyLayoutPreset("Debug Inspector".to_string()));
+        app.update();
+        assert_eq!(viewport_size(&mut app), 0.6);
+
+        app.world_mut().send_event(ResetLayout);
+        app.update();
+        assert_eq!(viewport_size(&mut app), 0.8);
+    }
+
+    #[test]
+    fn contributed_preset() {
+        let mut app = setup_app();
+        app.world_mut()
+            .resource_mut::<LayoutPresets>()
+            .register("Single Viewport", PaneLayout::pane("Viewport 3D"));
+
+        apply(&mut app, |commands| {
+            commands.apply_layout_preset("Single Viewport");
+        });
+        assert_eq!(pane_names(&mut app), ["Viewport 3D"]);
+
+        // Unknown presets leave the layout alone
+        apply(&mut app, |commands| commands.apply_layout_preset("Unknown"));
+        assert_eq!(pane_names(&mut app), ["Viewport 3D"]);
+    }
+}
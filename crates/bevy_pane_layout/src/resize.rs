@@ -0,0 +1,400 @@
+//! Dragging the resize handles between the areas of a divider.
+//!
+//! - Holding `Shift` while dragging snaps the handle to 25, 33, 50, 66 or 75% of the divider.
+//! - Double clicking a handle gives all areas of the divider the same size.
+//! - Hovered handles are highlighted, and a tooltip shows the resulting sizes while dragging.
+
+use bevy::prelude::*;
+use bevy_editor_styles::{TextRole, Theme};
+
+use crate::{
+    collapse::MinSizes,
+    layout::sized_children,
+    ui::{grip_style, resize_handle_root_style, resize_handle_style},
+    Divider, DragState, PaneLayoutCommandsExt, ResizeHandle, Size,
+};
+
+/// The positions within a divider a handle snaps to, as fractions of the divider.
+const SNAP_POINTS: [f32; 5] = [0.25, 1. / 3., 0.5, 2. / 3., 0.75];
+/// The maximum time between two clicks of a double click, in seconds.
+const DOUBLE_CLICK_TIME: f32 = 0.4;
+/// The distance between the pointer and the drag tooltip.
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(14., 14.);
+
+/// Finds the divider a handle is in, and the siblings on either side of it.
+fn handle_siblings(
+    handle: Entity,
+    parent_query: &Query<&Parent>,
+    children_query: &Query<&Children>,
+    divider_query: &Query<&Divider>,
+) -> Option<(Entity, Divider, usize)> {
+    let parent = parent_query.get(handle).ok()?.get();
+    let divider = *divider_query.get(parent).ok()?;
+    let index = children_query
+        .get(parent)
+        .ok()?
+        .iter()
+        .position(|entity| *entity == handle)?;
+    Some((parent, divider, index))
+}
+
+#[expect(clippy::too_many_arguments)]
+pub(crate) fn on_resize_drag_start(
+    trigger: Trigger<Pointer<DragStart>>,
+    mut commands: Commands,
+    mut drag_state: ResMut<DragState>,
+    theme: Res<Theme>,
+    parent_query: Query<&Parent>,
+    children_query: Query<&Children>,
+    divider_query: Query<&Divider>,
+    node_query: Query<&Node>,
+    size_query: Query<&Size>,
+    min_sizes: MinSizes,
+) {
+    let target = trigger.entity();
+    let Some((parent, divider, index)) =
+        handle_siblings(target, &parent_query, &children_query, &divider_query)
+    else {
+        return;
+    };
+    drag_state.is_dragging = true;
+
+    let parent_node_size = divider.main_axis(node_query.get(parent).unwrap().size());
+    let siblings = children_query.get(parent).unwrap();
+
+    let size_a = size_query.get(siblings[index - 1]).unwrap().0;
+    let size_b = size_query.get(siblings[index + 1]).unwrap().0;
+
+    // Panes can be squashed down to their header, in vertical dividers they collapse once the drag ends
+    let min_a = min_sizes.squashed(siblings[index - 1], divider);
+    let min_b = min_sizes.squashed(siblings[index + 1], divider);
+    drag_state.offset = 0.;
+    drag_state.min = (-size_a * parent_node_size) + min_a;
+    drag_state.max = (size_b * parent_node_size) - min_b;
+    drag_state.parent_node_size = parent_node_size;
+    drag_state.start_sizes = (size_a, size_b);
+    drag_state.start_position = sized_children(&siblings[..index], |sibling| {
+        size_query.get(sibling).ok().map(|size| size.0)
+    })
+    .map(|(_, size)| size)
+    .sum();
+
+    if let Some(tooltip) = drag_state.tooltip.take() {
+        commands.entity(tooltip).despawn_recursive();
+    }
+    let tooltip = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    padding: UiRect::axes(Val::Px(theme.spacing.sm), Val::Px(theme.spacing.xs)),
+                    ..default()
+                },
+                background_color: BackgroundColor(theme.colors.surface.raised),
+                border_radius: BorderRadius::all(Val::Px(theme.radius.medium)),
+                ..default()
+            },
+            GlobalZIndex(100),
+        ))
+        .with_child((
+            Text::new(""),
+            theme.typography.font(TextRole::Caption),
+            TextColor(theme.colors.text.primary),
+        ))
+        .id();
+    drag_state.tooltip = Some(tooltip);
+    update_tooltip(
+        &mut commands,
+        tooltip,
+        trigger.event().pointer_location.position,
+        (size_a, size_b),
+        parent_node_size,
+    );
+}
+
+#[expect(clippy::too_many_arguments)]
+pub(crate) fn on_resize_drag(
+    trigger: Trigger<Pointer<Drag>>,
+    mut commands: Commands,
+    mut drag_state: ResMut<DragState>,
+    input: Res<ButtonInput<KeyCode>>,
+    parent_query: Query<&Parent>,
+    children_query: Query<&Children>,
+    divider_query: Query<&Divider>,
+    mut size_query: Query<&mut Size>,
+) {
+    let target = trigger.entity();
+    let Some((parent, divider, index)) =
+        handle_siblings(target, &parent_query, &children_query, &divider_query)
+    else {
+        return;
+    };
+    if !drag_state.is_dragging || drag_state.parent_node_size <= 0. {
+        return;
+    }
+    let siblings = children_query.get(parent).unwrap();
+
+    let event = trigger.event();
+    drag_state.offset =
+        (drag_state.offset + divider.main_axis(event.delta)).clamp(drag_state.min, drag_state.max);
+
+    let offset = if input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        snap_offset(
+            drag_state.start_position,
+            drag_state.offset,
+            drag_state.min,
+            drag_state.max,
+            drag_state.parent_node_size,
+        )
+    } else {
+        drag_state.offset
+    };
+
+    let delta = offset / drag_state.parent_node_size;
+    let sizes = (
+        drag_state.start_sizes.0 + delta,
+        drag_state.start_sizes.1 - delta,
+    );
+    size_query.get_mut(siblings[index - 1]).unwrap().0 = sizes.0;
+    size_query.get_mut(siblings[index + 1]).unwrap().0 = sizes.1;
+
+    if let Some(tooltip) = drag_state.tooltip {
+        update_tooltip(
+            &mut commands,
+            tooltip,
+            event.pointer_location.position,
+            sizes,
+            drag_state.parent_node_size,
+        );
+    }
+}
+
+/// Ends a drag on [`Pointer<DragEnd>`] and [`Pointer<Cancel>`].
+pub(crate) fn on_resize_drag_end<E: std::fmt::Debug + Clone + Reflect>(
+    trigger: Trigger<Pointer<E>>,
+    mut commands: Commands,
+    mut drag_state: ResMut<DragState>,
+    children_query: Query<&Children>,
+    mut handle_query: Query<&mut BackgroundColor, With<ResizeHandle>>,
+) {
+    drag_state.is_dragging = false;
+    drag_state.offset = 0.;
+    if let Some(tooltip) = drag_state.tooltip.take() {
+        commands.entity(tooltip).despawn_recursive();
+    }
+    set_highlight(
+        trigger.entity(),
+        Color::NONE,
+        &children_query,
+        &mut handle_query,
+    );
+}
+
+/// Double clicking a handle gives all areas of its divider the same size.
+pub(crate) fn on_resize_click(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut last_click: Local<Option<f32>>,
+    time: Res<Time<Real>>,
+    parent_query: Query<&Parent>,
+    children_query: Query<&Children>,
+    size_query: Query<(), With<Size>>,
+) {
+    if trigger.event().button != PointerButton::Primary {
+        return;
+    }
+
+    let now = time.elapsed_secs();
+    let double_click = last_click.is_some_and(|last| now - last <= DOUBLE_CLICK_TIME);
+    *last_click = (!double_click).then_some(now);
+    if !double_click {
+        return;
+    }
+
+    let Ok(parent) = parent_query.get(trigger.entity()).map(Parent::get) else {
+        return;
+    };
+    let count = children_query
+        .get(parent)
+        .into_iter()
+        .flatten()
+        .filter(|child| size_query.contains(**child))
+        .count();
+    commands.set_pane_sizes(parent, vec![1.; count]);
+}
+
+/// Applies the [`Theme`] to the grips in the center of the resize handles.
+pub(crate) fn restyle_resize_grips(
+    theme: Res<Theme>,
+    mut handle_query: Query<
+        (&Children, &Parent, &mut Style, &mut BorderRadius),
+        With<ResizeHandle>,
+    >,
+    parent_query: Query<&Parent>,
+    divider_query: Query<&Divider>,
+    mut node_query: Query<
+        (&mut BackgroundColor, &mut BorderRadius, &mut Style),
+        Without<ResizeHandle>,
+    >,
+) {
+    for (children, root, mut style, mut border_radius) in &mut handle_query {
+        // The handle is wrapped in a node without size, within the divider
+        let Some(divider) = parent_query
+            .get(root.get())
+            .ok()
+            .and_then(|divider| divider_query.get(divider.get()).ok())
+        else {
+            continue;
+        };
+
+        set_style_size(&mut style, &resize_handle_style(&theme, *divider));
+        border_radius.set_if_neq(BorderRadius::all(Val::Px(theme.sizes.resize_handle / 2.)));
+        if let Ok((_, _, mut root_style)) = node_query.get_mut(root.get()) {
+            let root_target = resize_handle_root_style(&theme, *divider);
+            set_style_size(&mut root_style, &root_target);
+            if root_style.margin != root_target.margin {
+                root_style.margin = root_target.margin;
+            }
+        }
+
+        let grip_style = grip_style(&theme, *divider);
+        for grip in children {
+            let Ok((mut background_color, mut border_radius, mut style)) =
+                node_query.get_mut(*grip)
+            else {
+                continue;
+            };
+            background_color.set_if_neq(BackgroundColor(theme.colors.control.grip));
+            border_radius.set_if_neq(BorderRadius::all(Val::Px(theme.radius.small)));
+            set_style_size(&mut style, &grip_style);
+        }
+    }
+}
+
+/// Copies the width and height of `target`, only touching `style` if they differ.
+fn set_style_size(style: &mut Mut<Style>, target: &Style) {
+    if style.width != target.width || style.height != target.height {
+        style.width = target.width;
+        style.height = target.height;
+    }
+}
+
+/// Highlights a handle while the pointer is over it.
+pub(crate) fn on_resize_hover(
+    trigger: Trigger<Pointer<Move>>,
+    theme: Res<Theme>,
+    children_query: Query<&Children>,
+    mut handle_query: Query<&mut BackgroundColor, With<ResizeHandle>>,
+) {
+    set_highlight(
+        trigger.entity(),
+        theme.colors.control.handle,
+        &children_query,
+        &mut handle_query,
+    );
+}
+
+/// Removes the highlight once the pointer leaves a handle, unless it is being dragged.
+pub(crate) fn on_resize_out(
+    trigger: Trigger<Pointer<Out>>,
+    drag_state: Res<DragState>,
+    children_query: Query<&Children>,
+    mut handle_query: Query<&mut BackgroundColor, With<ResizeHandle>>,
+) {
+    if drag_state.is_dragging {
+        return;
+    }
+    set_highlight(
+        trigger.entity(),
+        Color::NONE,
+        &children_query,
+        &mut handle_query,
+    );
+}
+
+fn set_highlight(
+    handle: Entity,
+    color: Color,
+    children_query: &Query<&Children>,
+    handle_query: &mut Query<&mut BackgroundColor, With<ResizeHandle>>,
+) {
+    for child in children_query.get(handle).into_iter().flatten() {
+        if let Ok(mut background_color) = handle_query.get_mut(*child) {
+            background_color.set_if_neq(BackgroundColor(color));
+        }
+    }
+}
+
+/// Moves the tooltip next to the pointer and shows the sizes of the areas on either side of the handle.
+fn update_tooltip(
+    commands: &mut Commands,
+    tooltip: Entity,
+    pointer_position: Vec2,
+    sizes: (f32, f32),
+    parent_node_size: f32,
+) {
+    let position = pointer_position + TOOLTIP_OFFSET;
+    let text = format_sizes(sizes, parent_node_size);
+    commands.queue(move |world: &mut World| {
+        let Some(mut style) = world.get_mut::<Style>(tooltip) else {
+            return;
+        };
+        style.left = Val::Px(position.x);
+        style.top = Val::Px(position.y);
+
+        let Some(&label) = world
+            .get::<Children>(tooltip)
+            .and_then(|children| children.first())
+        else {
+            return;
+        };
+        if let Some(mut label) = world.get_mut::<Text>(label) {
+            label.0 = text;
+        }
+    });
+}
+
+/// Formats the sizes of two areas in pixels and as a percentage of their divider.
+fn format_sizes(sizes: (f32, f32), parent_node_size: f32) -> String {
+    let format = |size: f32| format!("{:.0}px ({:.0}%)", size * parent_node_size, size * 100.);
+    format!("{} | {}", format(sizes.0), format(sizes.1))
+}
+
+/// Snaps the offset of a handle that started at `start_position` to the closest snap point within `min..=max`.
+fn snap_offset(start_position: f32, offset: f32, min: f32, max: f32, parent_node_size: f32) -> f32 {
+    SNAP_POINTS
+        .iter()
+        .map(|point| (point - start_position) * parent_node_size)
+        .filter(|snapped| (min..=max).contains(snapped))
+        .min_by(|a, b| (a - offset).abs().total_cmp(&(b - offset).abs()))
+        .unwrap_or(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapping() {
+        // A handle at 40% of a 1000px divider
+        let snap = |offset, min, max| snap_offset(0.4, offset, min, max, 1000.);
+        let assert_near = |a: f32, b: f32| assert!((a - b).abs() < 1e-3, "{a} != {b}");
+
+        // Dragged 30px to the right, half is closest
+        assert_near(snap(30., -400., 600.), 100.);
+        // Dragged 80px to the left, a third is closest
+        assert_near(snap(-80., -400., 600.), -200. / 3.);
+        // Snap points out of reach are skipped
+        assert_near(snap(-40., -50., 600.), 100.);
+        // Without any snap point in reach the handle follows the pointer
+        assert_near(snap(-40., -50., 50.), -40.);
+    }
+
+    #[test]
+    fn tooltip_text() {
+        assert_eq!(
+            format_sizes((0.25, 0.75), 800.),
+            "200px (25%) | 600px (75%)"
+        );
+    }
+}
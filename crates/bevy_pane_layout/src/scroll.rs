@@ -0,0 +1,369 @@
+//! Scrolling the content of panes, with the mouse wheel, trackpads and draggable scrollbars.
+//!
+//! The scroll offset lives in [`PaneScroll`] on the pane root, so it survives the pane being collapsed.
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    picking::focus::HoverMap,
+    prelude::*,
+};
+use bevy_editor_styles::Theme;
+
+use crate::{Divider, PaneCollapsed, PaneStructure};
+
+/// How far a single line of a mouse wheel scrolls, in logical pixels.
+const LINE_HEIGHT: f32 = 20.;
+
+/// The scroll offset of the content of a pane in logical pixels, stored on the [`PaneRootNode`](crate::PaneRootNode).
+///
+/// Can be changed to scroll a pane programmatically, it is clamped to the size of the content.
+#[derive(Component, Clone, Copy, Default, Debug, PartialEq)]
+pub struct PaneScroll {
+    /// How far the content is scrolled to the left and up.
+    pub offset: Vec2,
+}
+
+/// The nodes used to scroll the content of a pane, stored on the pane root.
+#[derive(Component, Clone, Copy)]
+pub(crate) struct PaneScrollNodes {
+    /// The node with [`ScrollPosition`] wrapping the [`PaneContentNode`](crate::PaneContentNode).
+    viewport: Entity,
+    /// The tracks of the horizontal and vertical scrollbar.
+    tracks: [Entity; 2],
+    /// The thumbs of the horizontal and vertical scrollbar.
+    thumbs: [Entity; 2],
+}
+
+/// The draggable part of a scrollbar, moving along `axis`.
+#[derive(Component)]
+struct ScrollbarThumb {
+    pane: Entity,
+    axis: Divider,
+}
+
+/// Applies the [`Theme`] to the tracks and thumbs of the scrollbars.
+pub(crate) fn restyle_scrollbars(
+    theme: Res<Theme>,
+    nodes_query: Query<&PaneScrollNodes>,
+    mut background_query: Query<&mut BackgroundColor>,
+) {
+    for nodes in &nodes_query {
+        for (entities, color) in [
+            (nodes.tracks, theme.colors.control.track),
+            (nodes.thumbs, theme.colors.control.handle),
+        ] {
+            for entity in entities {
+                if let Ok(mut background_color) = background_query.get_mut(entity) {
+                    background_color.set_if_neq(BackgroundColor(color));
+                }
+            }
+        }
+    }
+}
+
+/// Spawns the viewport scrolling `content`, and the scrollbars next to it, into `body`.
+pub(crate) fn spawn_scroll_nodes(
+    commands: &mut Commands,
+    theme: &Theme,
+    pane_root: Entity,
+    body: Entity,
+    content: Entity,
+) {
+    let viewport = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    overflow: Overflow::scroll(),
+                    ..default()
+                },
+                ..default()
+            },
+            ScrollPosition::default(),
+        ))
+        .set_parent(body)
+        .add_child(content)
+        .id();
+
+    let [(horizontal_track, horizontal_thumb), (vertical_track, vertical_thumb)] =
+        [Divider::Horizontal, Divider::Vertical].map(|axis| {
+            let style = match axis {
+                Divider::Horizontal => Style {
+                    left: Val::Px(0.),
+                    right: Val::Px(theme.sizes.scrollbar),
+                    bottom: Val::Px(0.),
+                    height: Val::Px(theme.sizes.scrollbar),
+                    ..default()
+                },
+                Divider::Vertical => Style {
+                    top: Val::Px(0.),
+                    bottom: Val::Px(theme.sizes.scrollbar),
+                    right: Val::Px(0.),
+                    width: Val::Px(theme.sizes.scrollbar),
+                    ..default()
+                },
+            };
+
+            let track = commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        display: Display::None,
+                        ..style
+                    },
+                    background_color: BackgroundColor(theme.colors.control.track),
+                    border_radius: BorderRadius::all(Val::Px(theme.sizes.scrollbar / 2.)),
+                    z_index: ZIndex(1),
+                    ..default()
+                })
+                .set_parent(body)
+                .id();
+
+            let thumb = commands
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            width: Val::Percent(100.),
+                            height: Val::Percent(100.),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(theme.colors.control.handle),
+                        border_radius: BorderRadius::all(Val::Px(theme.sizes.scrollbar / 2.)),
+                        ..default()
+                    },
+                    ScrollbarThumb {
+                        pane: pane_root,
+                        axis,
+                    },
+                ))
+                .observe(on_thumb_drag)
+                .set_parent(track)
+                .id();
+
+            (track, thumb)
+        });
+
+    commands.entity(pane_root).insert((
+        PaneScroll::default(),
+        PaneScrollNodes {
+            viewport,
+            tracks: [horizontal_track, vertical_track],
+            thumbs: [horizontal_thumb, vertical_thumb],
+        },
+    ));
+}
+
+/// Dragging a thumb scrolls the content by the same fraction it moved along its track.
+fn on_thumb_drag(
+    trigger: Trigger<Pointer<Drag>>,
+    thumb_query: Query<(&ScrollbarThumb, &Parent)>,
+    node_query: Query<&Node>,
+    mut pane_query: Query<(&mut PaneScroll, &PaneStructure)>,
+) {
+    let Ok((thumb, track)) = thumb_query.get(trigger.entity()) else {
+        return;
+    };
+    let Ok((mut scroll, structure)) = pane_query.get_mut(thumb.pane) else {
+        return;
+    };
+    let (Ok(track), Ok(content)) = (
+        node_query.get(track.get()),
+        node_query.get(structure.content),
+    ) else {
+        return;
+    };
+
+    let track_size = thumb.axis.main_axis(track.size());
+    if track_size <= 0. {
+        return;
+    }
+    let delta = thumb.axis.main_axis(trigger.event().delta) * thumb.axis.main_axis(content.size())
+        / track_size;
+    match thumb.axis {
+        Divider::Horizontal => scroll.offset.x += delta,
+        Divider::Vertical => scroll.offset.y += delta,
+    }
+}
+
+/// Scrolls the panes under the pointers with the mouse wheel, holding shift scrolls sideways.
+pub(crate) fn scroll_panes_with_wheel(
+    mut wheel_events: EventReader<MouseWheel>,
+    hover_map: Option<Res<HoverMap>>,
+    input: Option<Res<ButtonInput<KeyCode>>>,
+    parent_query: Query<&Parent>,
+    mut scroll_query: Query<&mut PaneScroll>,
+) {
+    let Some(hover_map) = hover_map else {
+        wheel_events.clear();
+        return;
+    };
+
+    for event in wheel_events.read() {
+        let mut delta = match event.unit {
+            MouseScrollUnit::Line => Vec2::new(event.x, event.y) * LINE_HEIGHT,
+            MouseScrollUnit::Pixel => Vec2::new(event.x, event.y),
+        };
+        if input
+            .as_ref()
+            .is_some_and(|input| input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]))
+        {
+            delta = delta.yx();
+        }
+
+        // Scroll each pane once, no matter how many of its nodes are hovered
+        let mut panes: Vec<Entity> = hover_map
+            .values()
+            .flat_map(|hits| hits.keys())
+            .filter_map(|entity| {
+                std::iter::once(*entity)
+                    .chain(parent_query.iter_ancestors(*entity))
+                    .find(|entity| scroll_query.contains(*entity))
+            })
+            .collect();
+        panes.sort();
+        panes.dedup();
+
+        for pane in panes {
+            let mut scroll = scroll_query.get_mut(pane).unwrap();
+            scroll.offset = (scroll.offset - delta).max(Vec2::ZERO);
+        }
+    }
+}
+
+/// Clamps the scroll offset of panes to their content, applies it to the viewport and updates the scrollbars.
+pub(crate) fn apply_pane_scroll(
+    mut pane_query: Query<
+        (&mut PaneScroll, &PaneScrollNodes, &PaneStructure),
+        Without<PaneCollapsed>,
+    >,
+    node_query: Query<&Node>,
+    mut scroll_position_query: Query<&mut ScrollPosition>,
+    mut style_query: Query<&mut Style>,
+) {
+    for (mut scroll, nodes, structure) in &mut pane_query {
+        let (Ok(viewport), Ok(content)) = (
+            node_query.get(nodes.viewport),
+            node_query.get(structure.content),
+        ) else {
+            continue;
+        };
+        let viewport_size = viewport.size();
+        let content_size = content.size();
+        // The layout has not been computed yet
+        if viewport_size.cmple(Vec2::ZERO).any() {
+            continue;
+        }
+
+        let max_offset = (content_size - viewport_size).max(Vec2::ZERO);
+        let offset = scroll.offset.clamp(Vec2::ZERO, max_offset);
+        if scroll.offset != offset {
+            scroll.offset = offset;
+        }
+
+        if let Ok(mut scroll_position) = scroll_position_query.get_mut(nodes.viewport) {
+            if scroll_position.offset_x != offset.x || scroll_position.offset_y != offset.y {
+                scroll_position.offset_x = offset.x;
+                scroll_position.offset_y = offset.y;
+            }
+        }
+
+        for (index, axis) in [Divider::Horizontal, Divider::Vertical]
+            .into_iter()
+            .enumerate()
+        {
+            let overflowing = axis.main_axis(max_offset) > 0.5;
+            if let Ok(mut style) = style_query.get_mut(nodes.tracks[index]) {
+                let display = if overflowing {
+                    Display::Flex
+                } else {
+                    Display::None
+                };
+                if style.display != display {
+                    style.display = display;
+                }
+            }
+            if !overflowing {
+                continue;
+            }
+
+            let content_length = axis.main_axis(content_size);
+            let length = Val::Percent(axis.main_axis(viewport_size) / content_length * 100.);
+            let start = Val::Percent(axis.main_axis(offset) / content_length * 100.);
+            if let Ok(mut style) = style_query.get_mut(nodes.thumbs[index]) {
+                let (current_start, current_length) = match axis {
+                    Divider::Horizontal => (style.left, style.width),
+                    Divider::Vertical => (style.top, style.height),
+                };
+                if (current_start, current_length) == (start, length) {
+                    continue;
+                }
+                match axis {
+                    Divider::Horizontal => {
+                        style.left = start;
+                        style.width = length;
+                    }
+                    Divider::Vertical => {
+                        style.top = start;
+                        style.height = length;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{
+        picking::{backend::HitData, pointer::PointerId},
+        utils::HashMap,
+    };
+
+    use crate::testing::{pane, setup_app};
+
+    #[test]
+    fn wheel_scrolls_hovered_pane() {
+        let mut app = setup_app();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .add_event::<MouseWheel>();
+
+        let pane = pane(&mut app, "Properties");
+        let structure = *app.world().get::<PaneStructure>(pane).unwrap();
+
+        // Hover a widget within the content of the pane
+        let widget = app
+            .world_mut()
+            .spawn_empty()
+            .set_parent(structure.content)
+            .id();
+        let hit = HitData::new(Entity::PLACEHOLDER, 0., None, None);
+        app.insert_resource(HoverMap(HashMap::from([(
+            PointerId::Mouse,
+            HashMap::from([(widget, hit)]),
+        )])));
+
+        let wheel = |app: &mut App, y: f32| {
+            app.world_mut().send_event(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.,
+                y,
+                window: Entity::PLACEHOLDER,
+            });
+            app.update();
+            app.world().get::<PaneScroll>(pane).unwrap().offset
+        };
+
+        assert_eq!(wheel(&mut app, -2.), Vec2::new(0., 2. * LINE_HEIGHT));
+        // The offset can't go past the start of the content
+        assert_eq!(wheel(&mut app, 5.), Vec2::ZERO);
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::ShiftLeft);
+        assert_eq!(wheel(&mut app, -1.), Vec2::new(LINE_HEIGHT, 0.));
+    }
+}
@@ -0,0 +1,42 @@
+//! Fixtures shared by the tests of this crate.
+
+use bevy::prelude::*;
+use bevy_editor_styles::Theme;
+
+use crate::{PaneLayoutPlugin, PaneRootNode, RootPaneLayoutNode};
+
+/// Creates an app with the [`PaneLayoutPlugin`], without any layout yet.
+pub(crate) fn layout_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .init_resource::<Theme>()
+        .add_plugins(PaneLayoutPlugin);
+    app
+}
+
+/// Creates an app with the default layout:
+/// `Scene Tree` above `Properties` on the left, `Viewport 3D` on the right.
+pub(crate) fn setup_app() -> App {
+    let mut app = layout_app();
+    app.world_mut().spawn(RootPaneLayoutNode);
+    app.update();
+    app
+}
+
+/// Returns the root node of the pane called `name`.
+pub(crate) fn pane(app: &mut App, name: &str) -> Entity {
+    app.world_mut()
+        .query::<(Entity, &PaneRootNode)>()
+        .iter(app.world())
+        .find(|(_, pane)| pane.name() == name)
+        .unwrap()
+        .0
+}
+
+/// Runs `f` with the world's [`Commands`], then updates the app.
+pub(crate) fn apply(app: &mut App, f: impl FnOnce(&mut Commands)) {
+    let world = app.world_mut();
+    f(&mut world.commands());
+    world.flush();
+    app.update();
+}
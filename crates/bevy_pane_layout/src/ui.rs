@@ -1,39 +1,59 @@
 use bevy::{prelude::*, window::SystemCursorIcon, winit::cursor::CursorIcon};
-use bevy_editor_styles::Theme;
+use bevy_editor_styles::{StyleClass, TextRole, Theme};
 
 use crate::{
-    handlers::*, Divider, DragState, PaneAreaNode, PaneContentNode, PaneHeaderNode, PaneRootNode,
-    ResizeHandle, Size,
+    corner::spawn_pane_corners,
+    focus::{on_pane_hover, on_pane_pressed},
+    handlers::*,
+    resize::*,
+    scroll::spawn_scroll_nodes,
+    Divider, PaneAreaNode, PaneContentNode, PaneFooterNode, PaneHeaderNode, PaneHeaderSlot,
+    PaneRootNode, PaneStructure, ResizeHandle, RootPaneLayoutNode, Size,
 };
 
+/// The size of a collapsed pane, which only shows its header.
+pub(crate) fn collapsed_pane_size(theme: &Theme) -> f32 {
+    theme.sizes.pane_header + 2. * theme.sizes.pane_padding
+}
+
 pub(crate) fn spawn_pane<'a>(
     commands: &'a mut Commands,
     theme: &Theme,
     size: f32,
     name: impl Into<String>,
+) -> EntityCommands<'a> {
+    let root = commands.spawn_empty().id();
+    insert_pane(commands, root, theme, size, name)
+}
+
+/// Builds a pane on an existing (usually reserved) entity.
+pub(crate) fn insert_pane<'a>(
+    commands: &'a mut Commands,
+    root: Entity,
+    theme: &Theme,
+    size: f32,
+    name: impl Into<String>,
 ) -> EntityCommands<'a> {
     let name: String = name.into();
     // Unstyled root node
-    let root = commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    padding: UiRect::all(Val::Px(1.5)),
-                    ..default()
-                },
+    commands.entity(root).insert((
+        NodeBundle {
+            style: Style {
+                padding: UiRect::all(Val::Px(theme.sizes.pane_padding)),
                 ..default()
             },
-            Size(size),
-            PaneRootNode { name: name.clone() },
-        ))
-        .id();
+            ..default()
+        },
+        Size(size),
+        PaneRootNode { name: name.clone() },
+    ));
 
     // Area
     let area = commands
         .spawn((
             NodeBundle {
-                background_color: theme.pane_area_background_color,
-                border_radius: theme.border_radius,
+                background_color: BackgroundColor(theme.colors.surface.panel),
+                border_radius: BorderRadius::all(Val::Px(theme.radius.medium)),
                 style: Style {
                     overflow: Overflow::clip(),
                     width: Val::Percent(100.),
@@ -49,22 +69,20 @@ pub(crate) fn spawn_pane<'a>(
         .id();
 
     // Header
-    commands
+    let header = commands
         .spawn((
             NodeBundle {
-                background_color: theme.pane_header_background_color,
-                border_radius: theme.pane_header_border_radius,
                 style: Style {
-                    padding: UiRect::axes(Val::Px(5.), Val::Px(3.)),
                     width: Val::Percent(100.),
-                    height: Val::Px(27.),
                     align_items: AlignItems::Center,
                     ..default()
                 },
                 ..default()
             },
             PaneHeaderNode,
+            StyleClass::new("pane.header"),
         ))
+        .observe(on_pane_header_left_click)
         .observe(on_pane_header_right_click)
         .observe(on_pane_header_middle_click)
         .observe(
@@ -90,27 +108,150 @@ pub(crate) fn spawn_pane<'a>(
         .set_parent(area)
         .with_child((
             Text::new(name),
-            TextFont {
-                font_size: 14.,
+            theme.typography.font(TextRole::Label),
+            TextColor(theme.colors.text.primary),
+        ))
+        .id();
+
+    // Slots for pane specific widgets, after the title
+    let [header_left, header_center, header_right] = [
+        PaneHeaderSlot::Left,
+        PaneHeaderSlot::Center,
+        PaneHeaderSlot::Right,
+    ]
+    .map(|slot| {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_grow: if slot == PaneHeaderSlot::Center {
+                            1.
+                        } else {
+                            0.
+                        },
+                        height: Val::Percent(100.),
+                        margin: UiRect::left(Val::Px(theme.spacing.sm)),
+                        column_gap: Val::Px(theme.spacing.xs),
+                        align_items: AlignItems::Center,
+                        justify_content: match slot {
+                            PaneHeaderSlot::Left => JustifyContent::FlexStart,
+                            PaneHeaderSlot::Center => JustifyContent::Center,
+                            PaneHeaderSlot::Right => JustifyContent::FlexEnd,
+                        },
+                        ..default()
+                    },
+                    ..default()
+                },
+                slot,
+            ))
+            .set_parent(header)
+            .id()
+    });
+
+    // Body, holding the scrolled content and the scrollbars
+    let body = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_grow: 1.,
+                flex_basis: Val::Px(0.),
+                min_height: Val::Px(0.),
+                overflow: Overflow::clip(),
                 ..default()
             },
-        ));
+            ..default()
+        })
+        .set_parent(area)
+        .id();
 
-    // Content
-    commands
+    // Content, at least as large as the body and growing with what is put into it
+    let content = commands
         .spawn((
             NodeBundle {
                 style: Style {
-                    flex_grow: 1.,
+                    min_width: Val::Percent(100.),
+                    min_height: Val::Percent(100.),
+                    flex_shrink: 0.,
+                    align_self: AlignSelf::FlexStart,
                     ..default()
                 },
                 ..default()
             },
             PaneContentNode,
         ))
-        .set_parent(area);
+        .id();
+    spawn_scroll_nodes(commands, theme, root, body, content);
+
+    spawn_pane_corners(commands, root);
+
+    let mut ec = commands.entity(root);
+    ec.insert(PaneStructure {
+        root,
+        area,
+        header,
+        header_left,
+        header_center,
+        header_right,
+        content,
+        footer: None,
+    });
+    ec.observe(on_pane_hover).observe(on_pane_pressed);
+    ec
+}
+
+/// Adds a footer at the bottom of the area of a pane.
+pub(crate) fn spawn_footer<'a>(
+    commands: &'a mut Commands,
+    theme: &Theme,
+    area: Entity,
+) -> EntityCommands<'a> {
+    let mut ec = commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                column_gap: Val::Px(theme.spacing.xs),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        },
+        PaneFooterNode,
+        StyleClass::new("pane.footer"),
+    ));
+    ec.set_parent(area);
+    ec
+}
+
+/// Applies the [`Theme`] to the layout root and the areas of panes, which only read it when they
+/// are spawned.
+///
+/// The headers and footers of panes are styled by their [`StyleClass`].
+pub(crate) fn restyle_panes(
+    theme: Res<Theme>,
+    mut node_query: Query<(
+        &mut BackgroundColor,
+        &mut BorderRadius,
+        AnyOf<(&RootPaneLayoutNode, &PaneAreaNode)>,
+    )>,
+    mut pane_query: Query<&mut Style, With<PaneRootNode>>,
+) {
+    let padding = UiRect::all(Val::Px(theme.sizes.pane_padding));
+    for mut style in &mut pane_query {
+        if style.padding != padding {
+            style.padding = padding;
+        }
+    }
 
-    commands.entity(root)
+    for (mut background_color, mut border_radius, nodes) in &mut node_query {
+        match nodes {
+            (Some(_), _) => {
+                background_color.set_if_neq(BackgroundColor(theme.colors.surface.background));
+            }
+            _ => {
+                background_color.set_if_neq(BackgroundColor(theme.colors.surface.panel));
+                border_radius.set_if_neq(BorderRadius::all(Val::Px(theme.radius.medium)));
+            }
+        }
+    }
 }
 
 pub(crate) fn spawn_divider<'a>(
@@ -136,131 +277,40 @@ pub(crate) fn spawn_divider<'a>(
 
 pub(crate) fn spawn_resize_handle<'a>(
     commands: &'a mut Commands,
+    theme: &Theme,
     divider_parent: Divider,
 ) -> EntityCommands<'a> {
-    const SIZE: f32 = 7.;
     // Add a root node with zero size along the divider axis to avoid messing up the layout
     let mut ec = commands.spawn(NodeBundle {
-        style: Style {
-            width: match divider_parent {
-                Divider::Horizontal => Val::Px(SIZE),
-                Divider::Vertical => Val::Percent(100.),
-            },
-            height: match divider_parent {
-                Divider::Horizontal => Val::Percent(100.),
-                Divider::Vertical => Val::Px(SIZE),
-            },
-            // Abuse negative margins because setting width to zero is causing the child to be clipped
-            // presumably because of a bug in bevy_ui
-            margin: match divider_parent {
-                Divider::Horizontal => UiRect::horizontal(Val::Px(-SIZE / 2.)),
-                Divider::Vertical => UiRect::vertical(Val::Px(-SIZE / 2.)),
-            },
-            ..default()
-        },
+        style: resize_handle_root_style(theme, divider_parent),
         z_index: ZIndex(3),
         ..default()
     });
-    // Add the Resize
-    ec.with_child((
-        NodeBundle {
-            style: Style {
-                width: match divider_parent {
-                    Divider::Horizontal => Val::Px(SIZE),
-                    Divider::Vertical => Val::Percent(100.),
-                },
-                height: match divider_parent {
-                    Divider::Horizontal => Val::Percent(100.),
-                    Divider::Vertical => Val::Px(SIZE),
+    // Add the Resize, with a grip in its center
+    ec.with_children(|parent| {
+        parent
+            .spawn((
+                NodeBundle {
+                    style: resize_handle_style(theme, divider_parent),
+                    border_radius: BorderRadius::all(Val::Px(theme.sizes.resize_handle / 2.)),
+                    ..default()
                 },
+                ResizeHandle,
+            ))
+            .with_child(NodeBundle {
+                style: grip_style(theme, divider_parent),
+                background_color: BackgroundColor(theme.colors.control.grip),
+                border_radius: BorderRadius::all(Val::Px(theme.radius.small)),
                 ..default()
-            },
-            ..default()
-        },
-        ResizeHandle,
-    ))
-    .observe(
-        move |trigger: Trigger<Pointer<DragStart>>,
-              mut drag_state: ResMut<DragState>,
-              parent_query: Query<&Parent>,
-              children_query: Query<&Children>,
-              node_query: Query<&Node>,
-              size_query: Query<&Size>| {
-            drag_state.is_dragging = true;
-
-            let target = trigger.entity();
-            let parent = parent_query.get(target).unwrap().get();
-
-            let parent_node_size = node_query.get(parent).unwrap().size();
-            let parent_node_size = match divider_parent {
-                Divider::Horizontal => parent_node_size.x,
-                Divider::Vertical => parent_node_size.y,
-            };
-
-            let siblings = children_query.get(parent).unwrap();
-            // Find the index of this handle among its siblings
-            let index = siblings
-                .iter()
-                .position(|entity| *entity == target)
-                .unwrap();
-
-            let size_a = size_query.get(siblings[index - 1]).unwrap().0;
-            let size_b = size_query.get(siblings[index + 1]).unwrap().0;
-
-            let min_pane_size = 20.;
-            drag_state.offset = 0.;
-            drag_state.min = (-size_a * parent_node_size) + min_pane_size;
-            drag_state.max = (size_b * parent_node_size) - min_pane_size;
-            drag_state.parent_node_size = parent_node_size;
-        },
-    )
-    .observe(
-        move |trigger: Trigger<Pointer<Drag>>,
-              mut drag_state: ResMut<DragState>,
-              parent_query: Query<&Parent>,
-              children_query: Query<&Children>,
-              mut size_query: Query<&mut Size>| {
-            let target = trigger.entity();
-            let parent = parent_query.get(target).unwrap().get();
-            let siblings = children_query.get(parent).unwrap();
-            // Find the index of this handle among its siblings
-            let index = siblings
-                .iter()
-                .position(|entity| *entity == target)
-                .unwrap();
-
-            let delta = trigger.event().delta;
-            let delta = match divider_parent {
-                Divider::Horizontal => delta.x,
-                Divider::Vertical => delta.y,
-            };
-
-            let previous_offset = drag_state.offset;
-
-            drag_state.offset += delta;
-
-            drag_state.offset = drag_state.offset.clamp(drag_state.min, drag_state.max);
-
-            let clamped_delta = drag_state.offset - previous_offset;
-
-            size_query.get_mut(siblings[index - 1]).unwrap().0 +=
-                clamped_delta / drag_state.parent_node_size;
-            size_query.get_mut(siblings[index + 1]).unwrap().0 -=
-                clamped_delta / drag_state.parent_node_size;
-        },
-    )
-    .observe(
-        move |_trigger: Trigger<Pointer<DragEnd>>, mut drag_state: ResMut<DragState>| {
-            drag_state.is_dragging = false;
-            drag_state.offset = 0.;
-        },
-    )
-    .observe(
-        |_trigger: Trigger<Pointer<Cancel>>, mut drag_state: ResMut<DragState>| {
-            drag_state.is_dragging = false;
-            drag_state.offset = 0.;
-        },
-    )
+            });
+    })
+    .observe(on_resize_drag_start)
+    .observe(on_resize_drag)
+    .observe(on_resize_drag_end::<DragEnd>)
+    .observe(on_resize_drag_end::<Cancel>)
+    .observe(on_resize_click)
+    .observe(on_resize_hover)
+    .observe(on_resize_out)
     .observe(
         move |_trigger: Trigger<Pointer<Move>>,
               window_query: Query<Entity, With<Window>>,
@@ -286,3 +336,50 @@ pub(crate) fn spawn_resize_handle<'a>(
     );
     ec
 }
+
+/// The size of the grip in the center of a resize handle, across the axis of its divider.
+pub(crate) fn grip_style(theme: &Theme, divider_parent: Divider) -> Style {
+    let (length, thickness) = (
+        Val::Px(theme.sizes.grip_length),
+        Val::Px(theme.sizes.grip_thickness),
+    );
+    let (width, height) = match divider_parent {
+        Divider::Horizontal => (thickness, length),
+        Divider::Vertical => (length, thickness),
+    };
+    Style {
+        width,
+        height,
+        ..default()
+    }
+}
+
+/// The node wrapping a resize handle, which takes no room along the axis of its divider.
+pub(crate) fn resize_handle_root_style(theme: &Theme, divider_parent: Divider) -> Style {
+    let size = theme.sizes.resize_handle;
+    Style {
+        // Abuse negative margins because setting width to zero is causing the child to be clipped
+        // presumably because of a bug in bevy_ui
+        margin: match divider_parent {
+            Divider::Horizontal => UiRect::horizontal(Val::Px(-size / 2.)),
+            Divider::Vertical => UiRect::vertical(Val::Px(-size / 2.)),
+        },
+        ..resize_handle_style(theme, divider_parent)
+    }
+}
+
+/// The area a resize handle can be grabbed by, spanning its divider.
+pub(crate) fn resize_handle_style(theme: &Theme, divider_parent: Divider) -> Style {
+    let (thickness, span) = (Val::Px(theme.sizes.resize_handle), Val::Percent(100.));
+    let (width, height) = match divider_parent {
+        Divider::Horizontal => (thickness, span),
+        Divider::Vertical => (span, thickness),
+    };
+    Style {
+        width,
+        height,
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}